@@ -0,0 +1,75 @@
+use futures::{
+    channel::oneshot,
+    future::{FutureExt, Shared},
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+struct Arrivals {
+    expected: usize,
+    arrived: AtomicUsize,
+    all_arrived: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+/// Held by the harness: waits until every task reached the gate and then
+/// releases them all at once.
+pub struct StartBarrier {
+    all_arrived: oneshot::Receiver<()>,
+    release: oneshot::Sender<Instant>,
+}
+
+/// Held by every spawned task, resolves once the harness releases the barrier.
+#[derive(Clone)]
+pub struct StartGate {
+    arrivals: Arc<Arrivals>,
+    released: Shared<oneshot::Receiver<Instant>>,
+}
+
+impl StartBarrier {
+    pub fn new(expected: usize) -> (StartBarrier, StartGate) {
+        let (all_arrived_tx, all_arrived_rx) = oneshot::channel();
+        let (release_tx, release_rx) = oneshot::channel();
+        let barrier = StartBarrier {
+            all_arrived: all_arrived_rx,
+            release: release_tx,
+        };
+        let gate = StartGate {
+            arrivals: Arc::new(Arrivals {
+                expected,
+                arrived: AtomicUsize::new(0),
+                all_arrived: Mutex::new(Some(all_arrived_tx)),
+            }),
+            released: release_rx.shared(),
+        };
+        (barrier, gate)
+    }
+
+    /// Waits for all tasks to arrive, then releases them and returns the
+    /// release instant, which is where the measured region starts.
+    pub async fn release(self) -> Instant {
+        let _ = self.all_arrived.await;
+        let start = Instant::now();
+        let _ = self.release.send(start);
+        start
+    }
+}
+
+impl StartGate {
+    /// Registers the task as ready and waits for the release instant.
+    pub async fn wait(self) -> Instant {
+        let arrived = self.arrivals.arrived.fetch_add(1, Ordering::SeqCst) + 1;
+        if arrived == self.arrivals.expected {
+            if let Some(all_arrived) = self.arrivals.all_arrived.lock().unwrap().take() {
+                let _ = all_arrived.send(());
+            }
+        }
+        self.released
+            .await
+            .expect("start barrier dropped before releasing the tasks")
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{record::Record, stats};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Changes with a p-value above this are reported but never fail the run.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+fn baseline_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+pub fn save(dir: &Path, name: &str, records: &[Record]) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+    let path = baseline_path(dir, name);
+    let content = serde_json::to_string_pretty(records).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+pub fn load(dir: &Path, name: &str) -> Result<Vec<Record>, String> {
+    let path = baseline_path(dir, name);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("failed to read baseline {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("failed to parse baseline {}: {}", path.display(), e))
+}
+
+/// Identifies a scenario across runs: everything but the measurements.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    implementation: String,
+    executor: String,
+    threads: usize,
+    counters: usize,
+    ops_per_counter: usize,
+    read_ratio: u64,
+    mix: String,
+    hold: String,
+    payload: String,
+    shards: Option<usize>,
+}
+
+impl Key {
+    fn of(record: &Record) -> Self {
+        Key {
+            implementation: record.implementation.clone(),
+            executor: record.executor.clone(),
+            // Records from before the setting ran one worker per core.
+            threads: if record.threads == 0 {
+                record.host.cpus
+            } else {
+                record.threads
+            },
+            counters: record.counters,
+            ops_per_counter: record.ops_per_counter,
+            read_ratio: record.read_ratio.to_bits(),
+            mix: record.mix.clone(),
+            hold: record.hold.clone(),
+            payload: record.payload.clone(),
+            shards: record.shards,
+        }
+    }
+}
+
+fn throughputs(records: &[Record]) -> BTreeMap<Key, Vec<f64>> {
+    let mut samples = BTreeMap::new();
+    for record in records {
+        samples
+            .entry(Key::of(record))
+            .or_insert_with(Vec::new)
+            .push(record.throughput_ops_per_sec);
+    }
+    samples
+}
+
+struct Change {
+    key: Key,
+    baseline: f64,
+    current: f64,
+    percent: f64,
+    p_value: Option<f64>,
+    regression: bool,
+}
+
+pub struct Comparison {
+    changes: Vec<Change>,
+    missing: usize,
+}
+
+/// Compares mean throughput per scenario. A scenario regresses when it got
+/// slower by more than `threshold` percent and the change is significant;
+/// with fewer than two samples on either side significance can't be tested,
+/// so the threshold alone decides.
+pub fn compare(baseline: &[Record], current: &[Record], threshold: f64) -> Comparison {
+    let baseline = throughputs(baseline);
+    let current = throughputs(current);
+    let mut changes = Vec::new();
+    let mut missing = 0;
+    for (key, samples) in current {
+        let old_samples = match baseline.get(&key) {
+            Some(old_samples) => old_samples,
+            None => {
+                missing += 1;
+                continue;
+            }
+        };
+        let old = stats::mean(old_samples);
+        let new = stats::mean(&samples);
+        let percent = (new - old) / old * 100.0;
+        let p_value = stats::welch_t_test(old_samples, &samples);
+        let significant = p_value.map_or(true, |p| p < SIGNIFICANCE_LEVEL);
+        changes.push(Change {
+            key,
+            baseline: old,
+            current: new,
+            percent,
+            p_value,
+            regression: percent < -threshold && significant,
+        });
+    }
+    Comparison { changes, missing }
+}
+
+impl Comparison {
+    pub fn has_regression(&self) -> bool {
+        self.changes.iter().any(|change| change.regression)
+    }
+
+    /// Whether no scenario of the run was found in the baseline.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn write(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<20} {:>8} {:>7} {:>10} {:>10} {:>7} {:>16} {:>12} {:>10} {:>6} {:>14} {:>14} {:>9} {:>8}",
+            "implementation",
+            "executor",
+            "threads",
+            "counters",
+            "ops",
+            "ratio",
+            "mix",
+            "hold",
+            "payload",
+            "shards",
+            "baseline op/s",
+            "current op/s",
+            "change",
+            "p"
+        )?;
+        for change in &self.changes {
+            let p_value = change
+                .p_value
+                .map_or_else(|| "n/a".to_string(), |p| format!("{:.4}", p));
+            writeln!(
+                out,
+                "{:<20} {:>8} {:>7} {:>10} {:>10} {:>7} {:>16} {:>12} {:>10} {:>6} {:>14.0} {:>14.0} {:>+8.2}% {:>8}{}",
+                change.key.implementation,
+                change.key.executor,
+                change.key.threads,
+                change.key.counters,
+                change.key.ops_per_counter,
+                f64::from_bits(change.key.read_ratio),
+                change.key.mix,
+                change.key.hold,
+                change.key.payload,
+                change
+                    .key
+                    .shards
+                    .map_or_else(|| "-".to_string(), |shards| shards.to_string()),
+                change.baseline,
+                change.current,
+                change.percent,
+                p_value,
+                if change.regression {
+                    "  REGRESSION"
+                } else {
+                    ""
+                }
+            )?;
+        }
+        if self.missing > 0 {
+            writeln!(
+                out,
+                "{} scenario(s) are not present in the baseline",
+                self.missing
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+use async_learn::{
+    driver::{parse_count, validate_ratio, Trials},
+    executor::ExecutorKind,
+    hold::Hold,
+    mix::Mix,
+    payload::PayloadSpec,
+    report::OutputFormat,
+    sweep::Values,
+};
+use glob::Pattern;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "async-learn",
+    about = "Compares sync and async counters under a read/write workload"
+)]
+pub struct Opt {
+    /// Output format: text, csv or jsonl (JSON Lines)
+    #[structopt(
+        long = "format",
+        default_value = "text",
+        raw(possible_values = "&[\"text\", \"csv\", \"jsonl\"]")
+    )]
+    pub format: OutputFormat,
+
+    #[structopt(flatten)]
+    pub baseline: BaselineOpts,
+
+    #[structopt(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct BaselineOpts {
+    /// Saves the results under this baseline name
+    #[structopt(long = "save-baseline")]
+    pub save: Option<String>,
+
+    /// Compares the results against this previously saved baseline
+    #[structopt(long = "baseline")]
+    pub compare: Option<String>,
+
+    /// Throughput drop in percent that counts as a regression
+    #[structopt(
+        long = "threshold",
+        default_value = "5",
+        parse(try_from_str = "parse_threshold")
+    )]
+    pub threshold: f64,
+
+    /// Directory holding the saved baselines
+    #[structopt(
+        long = "baseline-dir",
+        default_value = "target/baselines",
+        parse(from_os_str)
+    )]
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Runs every selected implementation for every read/write ratio
+    #[structopt(name = "run")]
+    Run(RunOpts),
+
+    /// Replays the runs described in a TOML or JSON scenario file
+    #[structopt(name = "scenario")]
+    Scenario {
+        /// Path to the scenario file, format is picked by extension
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+
+    /// Runs the cartesian product of ratios, counters and operations
+    #[structopt(name = "sweep")]
+    Sweep(SweepOpts),
+
+    /// Runs one workload on a growing number of worker threads and prints
+    /// how each implementation scales
+    #[structopt(name = "scale")]
+    Scale(ScaleOpts),
+
+    /// Lists the registered implementations
+    #[structopt(name = "list")]
+    List {
+        /// Only list implementations matching one of these glob patterns
+        #[structopt(parse(try_from_str = "parse_pattern"))]
+        patterns: Vec<Pattern>,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub struct RunOpts {
+    /// Number of independent counters
+    #[structopt(
+        short = "c",
+        long = "counters",
+        default_value = "100",
+        parse(try_from_str = "parse_count")
+    )]
+    pub max_counters: usize,
+
+    /// Number of operations spawned per counter
+    #[structopt(
+        short = "o",
+        long = "ops",
+        default_value = "10000",
+        parse(try_from_str = "parse_count")
+    )]
+    pub per_counter_operations_cnt: usize,
+
+    /// Probability of an operation being a read, comma separated for several runs
+    #[structopt(
+        short = "r",
+        long = "ratio",
+        parse(try_from_str = "parse_ratio"),
+        raw(use_delimiter = "true")
+    )]
+    pub read_write_ratios: Vec<f64>,
+
+    /// Values behind the counters: u64, bytes:<size> (e.g. bytes:1k) or
+    /// map:<entries>, comma separated. Implementations that cannot hold a
+    /// payload are skipped for it
+    #[structopt(
+        short = "p",
+        long = "payload",
+        default_value = "u64",
+        raw(use_delimiter = "true")
+    )]
+    pub payloads: Vec<PayloadSpec>,
+
+    /// Worker threads of the executor (default: one per core)
+    #[structopt(short = "t", long = "threads", parse(try_from_str = "parse_count"))]
+    pub threads: Option<usize>,
+
+    #[structopt(flatten)]
+    pub workload: WorkloadOpts,
+
+    #[structopt(flatten)]
+    pub selection: Selection,
+
+    #[structopt(flatten)]
+    pub trials: Trials,
+}
+
+impl RunOpts {
+    pub fn read_write_ratios(&self) -> Vec<f64> {
+        if self.read_write_ratios.is_empty() {
+            vec![0.9, 0.5]
+        } else {
+            self.read_write_ratios.clone()
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct SweepOpts {
+    /// Read ratios as a list (0.5,0.9) or a start:end:step range
+    #[structopt(short = "r", long = "ratio", default_value = "0.1:0.9:0.1")]
+    pub read_write_ratios: Values<f64>,
+
+    /// Counter counts, fewer counters means hotter locks (e.g. 1:1000:*10)
+    #[structopt(short = "c", long = "counters", default_value = "1,10,100")]
+    pub max_counters: Values<usize>,
+
+    /// Operations spawned per counter, as a list or a range
+    #[structopt(short = "o", long = "ops", default_value = "10000")]
+    pub per_counter_operations_cnt: Values<usize>,
+
+    /// Values behind the counters: u64, bytes:<size> (e.g. bytes:1k) or
+    /// map:<entries>, comma separated. Implementations that cannot hold a
+    /// payload are skipped for it
+    #[structopt(
+        short = "p",
+        long = "payload",
+        default_value = "u64",
+        raw(use_delimiter = "true")
+    )]
+    pub payloads: Vec<PayloadSpec>,
+
+    #[structopt(flatten)]
+    pub workload: WorkloadOpts,
+
+    #[structopt(flatten)]
+    pub selection: Selection,
+
+    #[structopt(flatten)]
+    pub trials: Trials,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ScaleOpts {
+    /// Worker thread counts as a list or a range (default: powers of two up
+    /// to the number of cores)
+    #[structopt(short = "t", long = "threads")]
+    pub threads: Option<Values<usize>>,
+
+    /// Number of independent counters
+    #[structopt(
+        short = "c",
+        long = "counters",
+        default_value = "100",
+        parse(try_from_str = "parse_count")
+    )]
+    pub max_counters: usize,
+
+    /// Number of operations spawned per counter
+    #[structopt(
+        short = "o",
+        long = "ops",
+        default_value = "10000",
+        parse(try_from_str = "parse_count")
+    )]
+    pub per_counter_operations_cnt: usize,
+
+    /// Probability of an operation being a read
+    #[structopt(
+        short = "r",
+        long = "ratio",
+        default_value = "0.9",
+        parse(try_from_str = "parse_ratio")
+    )]
+    pub read_write_ratio: f64,
+
+    /// Value behind the counters: u64, bytes:<size> (e.g. bytes:1k) or
+    /// map:<entries>. Implementations that cannot hold it are skipped
+    #[structopt(short = "p", long = "payload", default_value = "u64")]
+    pub payload: PayloadSpec,
+
+    #[structopt(flatten)]
+    pub workload: WorkloadOpts,
+
+    #[structopt(flatten)]
+    pub selection: Selection,
+
+    #[structopt(flatten)]
+    pub trials: Trials,
+}
+
+/// Workload options shared by `run`, `sweep` and `scale`.
+#[derive(Debug, StructOpt)]
+pub struct WorkloadOpts {
+    /// Weights of the modifying operations, e.g. write=1,cas=1. Known
+    /// operations: write, increment, cas and update
+    #[structopt(long = "mix", default_value = "write=1")]
+    pub mix: Mix,
+
+    /// What writers do while holding the lock: none, spin:<duration>, yield,
+    /// sleep:<duration> or block:<duration> (e.g. spin:500ns, sleep:1ms).
+    /// Implementations that cannot run it are skipped
+    #[structopt(long = "hold", default_value = "none")]
+    pub hold: Hold,
+
+    /// Shards the sharded implementations split the value into (default:
+    /// one per core)
+    #[structopt(long = "shards", parse(try_from_str = "parse_count"))]
+    pub shards: Option<usize>,
+
+    /// Executor the operations are spawned on: tokio, tokio-current-thread,
+    /// async-std, thread-pool or thread-per-core. `scale` needs one with a
+    /// configurable number of threads
+    #[structopt(short = "e", long = "executor", default_value = "tokio")]
+    pub executor: ExecutorKind,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Selection {
+    /// Glob patterns of implementations to run, comma separated (default: all)
+    #[structopt(
+        short = "i",
+        long = "impl",
+        parse(try_from_str = "parse_pattern"),
+        raw(use_delimiter = "true")
+    )]
+    pub include: Vec<Pattern>,
+
+    /// Glob patterns of implementations to skip, comma separated
+    #[structopt(
+        short = "x",
+        long = "exclude",
+        parse(try_from_str = "parse_pattern"),
+        raw(use_delimiter = "true")
+    )]
+    pub exclude: Vec<Pattern>,
+}
+
+fn parse_ratio(s: &str) -> Result<f64, String> {
+    let ratio = s
+        .parse::<f64>()
+        .map_err(|e| format!("`{}` is not a valid ratio: {}", s, e))?;
+    validate_ratio(ratio)
+}
+
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    let threshold = s
+        .parse::<f64>()
+        .map_err(|e| format!("`{}` is not a valid threshold: {}", s, e))?;
+    if !threshold.is_finite() {
+        return Err(format!("threshold {} must be a finite number", threshold));
+    }
+    if threshold < 0.0 {
+        return Err(format!("threshold {} must not be negative", threshold));
+    }
+    Ok(threshold)
+}
+
+fn parse_pattern(s: &str) -> Result<Pattern, String> {
+    Pattern::new(s).map_err(|e| format!("`{}` is not a valid glob pattern: {}", s, e))
+}
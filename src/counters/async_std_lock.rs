@@ -0,0 +1,99 @@
+use super::{swap_if_equal, CounterTrait};
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use async_std::sync::RwLock as AsyncRwLock;
+use async_trait::async_trait;
+use std::{sync::Arc, time::Instant};
+
+pub struct AsyncCounter<P = u64> {
+    inner: Arc<AsyncRwLock<P>>,
+    hold: Hold,
+}
+
+impl<P: Payload> AsyncCounter<P> {
+    fn new(initial: P, hold: Hold) -> Self {
+        AsyncCounter {
+            inner: Arc::new(AsyncRwLock::new(initial)),
+            hold,
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let val = self.inner.read().await;
+        let acquired = Instant::now();
+        Timed::new(val.clone(), acquired)
+    }
+
+    async fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let mut curr = self.inner.write().await;
+        let acquired = Instant::now();
+        self.hold.wait().await;
+        Timed::new(f(&mut *curr), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment).await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new)).await
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr)).await
+    }
+}
+
+impl<P> Clone for AsyncCounter<P> {
+    fn clone(&self) -> AsyncCounter<P> {
+        AsyncCounter {
+            inner: Arc::clone(&self.inner),
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for AsyncCounter<P> {
+    fn name() -> &'static str {
+        "AsyncCounter"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        AsyncCounter::new(initial, hold)
+    }
+
+    fn supports_hold(_: Hold) -> bool {
+        true
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    register_payloads!(registry, AsyncCounter);
+}
@@ -0,0 +1,153 @@
+use super::CounterTrait;
+use crate::{hold::Hold, latency::Timed, registry::Registry};
+use async_trait::async_trait;
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Memory orderings used by `AtomicCounter` loads, stores and
+/// read-modify-writes. Failed compare-exchanges use `LOAD`.
+pub trait Orderings: Send + Sync + 'static {
+    const NAME: &'static str;
+    const LOAD: Ordering;
+    const STORE: Ordering;
+    const RMW: Ordering;
+}
+
+pub struct Relaxed;
+
+impl Orderings for Relaxed {
+    const NAME: &'static str = "AtomicRelaxed";
+    const LOAD: Ordering = Ordering::Relaxed;
+    const STORE: Ordering = Ordering::Relaxed;
+    const RMW: Ordering = Ordering::Relaxed;
+}
+
+pub struct AcquireRelease;
+
+impl Orderings for AcquireRelease {
+    const NAME: &'static str = "AtomicAcquireRelease";
+    const LOAD: Ordering = Ordering::Acquire;
+    const STORE: Ordering = Ordering::Release;
+    const RMW: Ordering = Ordering::AcqRel;
+}
+
+pub struct SeqCst;
+
+impl Orderings for SeqCst {
+    const NAME: &'static str = "AtomicSeqCst";
+    const LOAD: Ordering = Ordering::SeqCst;
+    const STORE: Ordering = Ordering::SeqCst;
+    const RMW: Ordering = Ordering::SeqCst;
+}
+
+/// Lock-free baseline: there is nothing to wait for, so the acquisition
+/// instant is taken right before the atomic access.
+pub struct AtomicCounter<O> {
+    inner: Arc<AtomicU64>,
+    orderings: PhantomData<O>,
+}
+
+impl<O: Orderings> AtomicCounter<O> {
+    fn new(initial: u64) -> Self {
+        AtomicCounter {
+            inner: Arc::new(AtomicU64::new(initial)),
+            orderings: PhantomData,
+        }
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        let acquired = Instant::now();
+        Timed::new(self.inner.load(O::LOAD), acquired)
+    }
+
+    async fn set(&self, val: u64) -> Timed<()> {
+        let acquired = Instant::now();
+        self.inner.store(val, O::STORE);
+        Timed::new((), acquired)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        let acquired = Instant::now();
+        self.inner.fetch_add(1, O::RMW);
+        Timed::new((), acquired)
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        let acquired = Instant::now();
+        let result = self
+            .inner
+            .compare_exchange(current, new, O::RMW, O::LOAD)
+            .map(|_| ());
+        Timed::new(result, acquired)
+    }
+
+    async fn update(&self, f: impl Fn(&u64) -> u64) -> Timed<()> {
+        let acquired = Instant::now();
+        let mut current = self.inner.load(O::LOAD);
+        loop {
+            match self
+                .inner
+                .compare_exchange_weak(current, f(&current), O::RMW, O::LOAD)
+            {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+        Timed::new((), acquired)
+    }
+}
+
+impl<O> Clone for AtomicCounter<O> {
+    fn clone(&self) -> AtomicCounter<O> {
+        AtomicCounter {
+            inner: Arc::clone(&self.inner),
+            orderings: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<O: Orderings> CounterTrait for AtomicCounter<O> {
+    fn name() -> &'static str {
+        O::NAME
+    }
+
+    fn new(initial: u64, _: Hold) -> Self {
+        AtomicCounter::new(initial)
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        self.get().await
+    }
+
+    async fn set(&self, value: u64) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&u64) -> u64 + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    registry.register::<u64, AtomicCounter<Relaxed>>();
+    registry.register::<u64, AtomicCounter<AcquireRelease>>();
+    registry.register::<u64, AtomicCounter<SeqCst>>();
+}
@@ -0,0 +1,305 @@
+use super::{swap_if_equal, thread_index, CounterTrait};
+use crate::{hold::Hold, latency::Timed, registry::Registry};
+use async_trait::async_trait;
+use crossbeam::utils::CachePadded;
+use std::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Operations in flight beyond this many skip publication and take the lock
+/// directly.
+const SLOTS: usize = 64;
+
+const EMPTY: usize = 0;
+const CLAIMED: usize = 1;
+const DONE: usize = 2;
+const REQUEST_GET: usize = 3;
+const REQUEST_SET: usize = 4;
+const REQUEST_INCREMENT: usize = 5;
+const REQUEST_CAS: usize = 6;
+const REQUEST_UPDATE: usize = 7;
+
+/// Closure of an update request, published by address. The requesting
+/// thread spins until its request is done, so the closure outlives its use
+/// by the combiner.
+type UpdateFn<'a> = &'a (dyn Fn(&u64) -> u64 + Sync);
+
+/// Publication record, claimed by a thread for one operation at a time.
+/// Tasks never await while an operation is pending, so a thread holds at
+/// most one slot and only as many slots are busy as threads are operating.
+struct Slot {
+    state: AtomicUsize,
+    /// New value for set and CAS, `*const UpdateFn` for update.
+    argument: AtomicU64,
+    /// Expected value for CAS.
+    expected: AtomicU64,
+    /// Value before the operation.
+    result: AtomicU64,
+    /// Nanoseconds since `FlatCombiningState::created` the request ran at.
+    executed_at: AtomicU64,
+}
+
+struct FlatCombiningState {
+    lock: AtomicBool,
+    slots: Vec<CachePadded<Slot>>,
+    value: UnsafeCell<u64>,
+    created: Instant,
+    passes: AtomicU64,
+    combined: AtomicU64,
+    /// Operations that found every slot busy and took the lock directly.
+    fallbacks: AtomicU64,
+}
+
+// `value` is only accessed by the thread holding `lock`.
+unsafe impl Sync for FlatCombiningState {}
+
+impl FlatCombiningState {
+    fn try_lock(&self) -> bool {
+        !self.lock.load(Ordering::Relaxed) && !self.lock.swap(true, Ordering::Acquire)
+    }
+
+    fn unlock(&self) {
+        self.lock.store(false, Ordering::Release);
+    }
+
+    fn now(&self) -> u64 {
+        self.created.elapsed().as_nanos() as u64
+    }
+
+    /// Returns the value before the operation. Must be called with `lock`
+    /// held.
+    fn apply(&self, request: usize, argument: u64, expected: u64) -> u64 {
+        let value = unsafe { &mut *self.value.get() };
+        let previous = *value;
+        match request {
+            REQUEST_SET => *value = argument,
+            REQUEST_INCREMENT => *value = value.wrapping_add(1),
+            REQUEST_CAS => {
+                let _ = swap_if_equal(value, expected, argument);
+            }
+            REQUEST_UPDATE => {
+                let f = unsafe { *(argument as usize as *const UpdateFn) };
+                *value = f(&previous);
+            }
+            _ => {}
+        }
+        previous
+    }
+
+    /// Executes every published request. Must be called with `lock` held.
+    fn combine(&self) {
+        let mut combined = 0;
+        for slot in &self.slots {
+            let request = slot.state.load(Ordering::Acquire);
+            if request != EMPTY && request != CLAIMED && request != DONE {
+                let argument = slot.argument.load(Ordering::Relaxed);
+                let expected = slot.expected.load(Ordering::Relaxed);
+                slot.executed_at.store(self.now(), Ordering::Relaxed);
+                slot.result
+                    .store(self.apply(request, argument, expected), Ordering::Relaxed);
+                slot.state.store(DONE, Ordering::Release);
+                combined += 1;
+            }
+        }
+        self.passes.fetch_add(1, Ordering::Relaxed);
+        self.combined.fetch_add(combined, Ordering::Relaxed);
+    }
+
+    /// Claims a free slot for one operation. The search starts at the calling
+    /// thread's usual slot, so a thread keeps publishing through the same
+    /// slot as long as no other thread is using it.
+    fn claim_slot(&self) -> Option<&Slot> {
+        let start = thread_index();
+        (0..SLOTS)
+            .map(|offset| &*self.slots[(start + offset) % SLOTS])
+            .find(|slot| {
+                slot.state.load(Ordering::Relaxed) == EMPTY
+                    && slot
+                        .state
+                        .compare_exchange(EMPTY, CLAIMED, Ordering::Acquire, Ordering::Relaxed)
+                        .is_ok()
+            })
+    }
+
+    fn execute(&self, request: usize, argument: u64, expected: u64) -> (u64, Instant) {
+        let slot = match self.claim_slot() {
+            Some(slot) => slot,
+            None => {
+                self.fallbacks.fetch_add(1, Ordering::Relaxed);
+                while !self.try_lock() {
+                    atomic::spin_loop_hint();
+                }
+                let acquired = Instant::now();
+                let result = self.apply(request, argument, expected);
+                self.unlock();
+                return (result, acquired);
+            }
+        };
+        slot.argument.store(argument, Ordering::Relaxed);
+        slot.expected.store(expected, Ordering::Relaxed);
+        slot.state.store(request, Ordering::Release);
+        loop {
+            if slot.state.load(Ordering::Acquire) == DONE {
+                let result = slot.result.load(Ordering::Relaxed);
+                let executed_at = slot.executed_at.load(Ordering::Relaxed);
+                // Hands the slot over to the next claimant.
+                slot.state.store(EMPTY, Ordering::Release);
+                return (result, self.created + Duration::from_nanos(executed_at));
+            }
+            if self.try_lock() {
+                self.combine();
+                self.unlock();
+            } else {
+                atomic::spin_loop_hint();
+            }
+        }
+    }
+}
+
+/// Flat combining: threads publish their operation in a free slot and
+/// whichever thread grabs the lock executes all published operations in one
+/// pass, so the value stays in the combiner's cache under heavy contention.
+pub struct FlatCombiningCounter {
+    inner: Arc<FlatCombiningState>,
+}
+
+impl FlatCombiningCounter {
+    fn new(initial: u64) -> Self {
+        let slots = (0..SLOTS)
+            .map(|_| {
+                CachePadded::new(Slot {
+                    state: AtomicUsize::new(EMPTY),
+                    argument: AtomicU64::new(0),
+                    expected: AtomicU64::new(0),
+                    result: AtomicU64::new(0),
+                    executed_at: AtomicU64::new(0),
+                })
+            })
+            .collect();
+        FlatCombiningCounter {
+            inner: Arc::new(FlatCombiningState {
+                lock: AtomicBool::new(false),
+                slots,
+                value: UnsafeCell::new(initial),
+                created: Instant::now(),
+                passes: AtomicU64::new(0),
+                combined: AtomicU64::new(0),
+                fallbacks: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        let (val, acquired) = self.inner.execute(REQUEST_GET, 0, 0);
+        Timed::new(val, acquired)
+    }
+
+    async fn set(&self, val: u64) -> Timed<()> {
+        let (_, acquired) = self.inner.execute(REQUEST_SET, val, 0);
+        Timed::new((), acquired)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        let (_, acquired) = self.inner.execute(REQUEST_INCREMENT, 0, 0);
+        Timed::new((), acquired)
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        let (previous, acquired) = self.inner.execute(REQUEST_CAS, new, current);
+        let result = if previous == current {
+            Ok(())
+        } else {
+            Err(previous)
+        };
+        Timed::new(result, acquired)
+    }
+
+    async fn update(&self, f: impl Fn(&u64) -> u64 + Sync) -> Timed<()> {
+        let f: UpdateFn = &f;
+        let argument = &f as *const UpdateFn as usize as u64;
+        let (_, acquired) = self.inner.execute(REQUEST_UPDATE, argument, 0);
+        Timed::new((), acquired)
+    }
+}
+
+impl Clone for FlatCombiningCounter {
+    fn clone(&self) -> FlatCombiningCounter {
+        FlatCombiningCounter {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[async_trait]
+impl CounterTrait for FlatCombiningCounter {
+    fn name() -> &'static str {
+        "FlatCombining"
+    }
+
+    fn new(initial: u64, _: Hold) -> Self {
+        FlatCombiningCounter::new(initial)
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        self.get().await
+    }
+
+    async fn set(&self, value: u64) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&u64) -> u64 + Send + Sync,
+    {
+        self.update(f).await
+    }
+
+    fn metrics(&self) -> Vec<(&'static str, u64)> {
+        vec![
+            (
+                "combining_passes",
+                self.inner.passes.load(Ordering::Relaxed),
+            ),
+            ("combined_ops", self.inner.combined.load(Ordering::Relaxed)),
+            ("fallback_ops", self.inner.fallbacks.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    registry.register::<u64, FlatCombiningCounter>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::thread;
+
+    #[test]
+    fn threads_started_one_after_another_reuse_slots() {
+        let counter = FlatCombiningCounter::new(0);
+        for _ in 0..2 * SLOTS {
+            let counter = counter.clone();
+            thread::spawn(move || block_on(counter.increment()))
+                .join()
+                .unwrap();
+        }
+        assert_eq!(block_on(counter.get()).value, 2 * SLOTS as u64);
+        assert_eq!(counter.inner.fallbacks.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,204 @@
+use super::{swap_if_equal, CounterTrait};
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use async_trait::async_trait;
+use crossbeam::utils::CachePadded;
+use std::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{self, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+/// Left-right concurrency control (Ramalhete and Correia), the scheme behind
+/// evmap. Two copies of the value are kept: readers are wait-free and read
+/// the copy `left_right` points at, the single writer updates the other
+/// copy, flips `left_right`, waits until no reader can still be on the old
+/// copy and then updates that one too.
+struct LeftRightState<P> {
+    instances: [UnsafeCell<P>; 2],
+    left_right: AtomicUsize,
+    version_index: AtomicUsize,
+    read_indicators: [CachePadded<AtomicUsize>; 2],
+    writer: Mutex<()>,
+}
+
+// Readers only touch the instance `left_right` points at, and a writer only
+// touches an instance after every reader that could see it has departed.
+unsafe impl<P: Send + Sync> Sync for LeftRightState<P> {}
+
+impl<P: Payload> LeftRightState<P> {
+    fn read(&self) -> P {
+        let version = self.version_index.load(Ordering::SeqCst);
+        self.read_indicators[version].fetch_add(1, Ordering::SeqCst);
+        let instance = self.left_right.load(Ordering::SeqCst);
+        let val = unsafe { (*self.instances[instance].get()).clone() };
+        self.read_indicators[version].fetch_sub(1, Ordering::SeqCst);
+        val
+    }
+
+    /// Applies `f` to the copy readers cannot see, publishes it and then
+    /// brings the other copy up to date. Must be called with `writer` held.
+    fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> R {
+        let instance = self.left_right.load(Ordering::SeqCst);
+        let (result, val) = unsafe {
+            let standby = &mut *self.instances[1 - instance].get();
+            (f(standby), standby.clone())
+        };
+        self.left_right.store(1 - instance, Ordering::SeqCst);
+        self.toggle_version_and_wait();
+        unsafe {
+            *self.instances[instance].get() = val;
+        }
+        result
+    }
+
+    fn toggle_version_and_wait(&self) {
+        let previous = self.version_index.load(Ordering::SeqCst);
+        let next = 1 - previous;
+        self.wait_for_readers(next);
+        self.version_index.store(next, Ordering::SeqCst);
+        self.wait_for_readers(previous);
+    }
+
+    fn wait_for_readers(&self, version: usize) {
+        while self.read_indicators[version].load(Ordering::SeqCst) != 0 {
+            atomic::spin_loop_hint();
+        }
+    }
+}
+
+pub struct LeftRightCounter<P = u64> {
+    inner: Arc<LeftRightState<P>>,
+}
+
+impl<P: Payload> LeftRightCounter<P> {
+    fn new(initial: P) -> Self {
+        LeftRightCounter {
+            inner: Arc::new(LeftRightState {
+                instances: [UnsafeCell::new(initial.clone()), UnsafeCell::new(initial)],
+                left_right: AtomicUsize::new(0),
+                version_index: AtomicUsize::new(0),
+                read_indicators: [
+                    CachePadded::new(AtomicUsize::new(0)),
+                    CachePadded::new(AtomicUsize::new(0)),
+                ],
+                writer: Mutex::new(()),
+            }),
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let acquired = Instant::now();
+        Timed::new(self.inner.read(), acquired)
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let _writer = self.inner.writer.lock().unwrap();
+        let acquired = Instant::now();
+        Timed::new(self.inner.write(f), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment)
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new))
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr))
+    }
+}
+
+impl<P> Clone for LeftRightCounter<P> {
+    fn clone(&self) -> LeftRightCounter<P> {
+        LeftRightCounter {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for LeftRightCounter<P> {
+    fn name() -> &'static str {
+        "LeftRight"
+    }
+
+    fn new(initial: P, _: Hold) -> Self {
+        LeftRightCounter::new(initial)
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    register_payloads!(registry, LeftRightCounter);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counters::tests::{INCREMENTS, THREADS};
+    use futures::executor::block_on;
+    use std::thread;
+
+    #[test]
+    fn keeps_every_increment_under_concurrent_reads() {
+        let counter = LeftRightCounter::new(0u64);
+        let writers = (0..THREADS)
+            .map(|_| {
+                let counter = counter.clone();
+                thread::spawn(move || {
+                    for _ in 0..INCREMENTS {
+                        block_on(counter.increment());
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let reader = {
+            let counter = counter.clone();
+            thread::spawn(move || {
+                // Reads never go backwards, even while a writer switches copies.
+                let mut last = 0;
+                for _ in 0..INCREMENTS {
+                    let val = block_on(counter.get()).value;
+                    assert!(val >= last, "read {} after {}", val, last);
+                    last = val;
+                }
+            })
+        };
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        reader.join().unwrap();
+        assert_eq!(block_on(counter.get()).value, THREADS as u64 * INCREMENTS);
+    }
+}
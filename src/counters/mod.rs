@@ -0,0 +1,269 @@
+/// Registers `$counter<.., P>` for every built-in payload `P`, the payload
+/// being the counter's last type parameter.
+macro_rules! register_payloads {
+    ($registry:expr, $counter:ident $(<$($param:ty),+>)?) => {
+        $registry.register::<u64, $counter<$($($param,)+)? u64>>();
+        $registry
+            .register::<crate::payload::Bytes, $counter<$($($param,)+)? crate::payload::Bytes>>();
+        $registry
+            .register::<crate::payload::Map, $counter<$($($param,)+)? crate::payload::Map>>();
+    };
+}
+
+mod async_std_lock;
+mod atomic;
+mod flat_combining;
+mod left_right;
+mod parking_lot_lock;
+mod rcu;
+mod seqlock;
+mod sharded;
+mod sharded_lock;
+mod spin;
+mod std_lock;
+mod tokio_lock;
+
+pub use self::{
+    async_std_lock::AsyncCounter,
+    atomic::{AcquireRelease, AtomicCounter, Relaxed, SeqCst},
+    flat_combining::FlatCombiningCounter,
+    left_right::LeftRightCounter,
+    parking_lot_lock::{ParkingLotFairMutex, ParkingLotMutex, ParkingLotRwLock},
+    rcu::RcuCounter,
+    seqlock::SeqLockCounter,
+    sharded::ShardedCounter,
+    sharded_lock::ShardedLockCounter,
+    spin::{McsLock, RawSpinLock, SpinCounter, TasLock, TicketLock, TtasLock},
+    std_lock::Counter,
+    tokio_lock::{TokioMutex, TokioRwLock, TokioSemaphore},
+};
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Shared value of type `P` behind some synchronization primitive.
+#[async_trait]
+pub trait CounterTrait<P: Payload = u64>: Clone + 'static + Send {
+    /// Shared state holding `initial`. Writers run `hold` inside their
+    /// critical section, implementations only get the models they support.
+    fn new(initial: P, hold: Hold) -> Self;
+    fn name() -> &'static str;
+
+    /// Like `new`, for implementations that split the value into `shards`
+    /// parts. The others ignore the count.
+    fn with_shards(initial: P, hold: Hold, _: usize) -> Self {
+        Self::new(initial, hold)
+    }
+
+    /// Whether `with_shards` uses the count, so that reports only carry it
+    /// where it makes a difference.
+    fn uses_shards() -> bool {
+        false
+    }
+
+    /// Whether writers can run `hold` while holding the value. Only lock
+    /// based implementations have a critical section to stretch.
+    fn supports_hold(hold: Hold) -> bool {
+        hold == Hold::None
+    }
+
+    async fn get(&self) -> Timed<P>;
+    async fn set(&self, value: P) -> Timed<()>;
+
+    /// Adds one to the value, see `Payload::increment`.
+    async fn increment(&self) -> Timed<()>;
+
+    /// Replaces the value with `new` if it equals `current`, otherwise hands
+    /// back the value it found.
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>>;
+
+    /// Replaces the value with `f` applied to it, with no other write in
+    /// between. Optimistic implementations retry on a conflicting write and
+    /// may call `f` more than once, like `AtomicU64::fetch_update`.
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync;
+
+    /// Implementation specific counters, such as retries, accumulated over
+    /// the lifetime of the shared state. Summed over all counters of a run.
+    fn metrics(&self) -> Vec<(&'static str, u64)> {
+        Vec::new()
+    }
+}
+
+/// `compare_and_swap` for implementations with exclusive access to the
+/// value.
+pub(crate) fn swap_if_equal<P: Payload>(value: &mut P, current: P, new: P) -> Result<(), P> {
+    if *value == current {
+        *value = new;
+        Ok(())
+    } else {
+        Err(value.clone())
+    }
+}
+
+/// Registers every implementation in this module tree.
+pub fn register_builtins(registry: &mut Registry) {
+    std_lock::register(registry);
+    async_std_lock::register(registry);
+    parking_lot_lock::register(registry);
+    tokio_lock::register(registry);
+    atomic::register(registry);
+    seqlock::register(registry);
+    sharded::register(registry);
+    rcu::register(registry);
+    sharded_lock::register(registry);
+    left_right::register(registry);
+    spin::register(registry);
+    flat_combining::register(registry);
+}
+
+/// Small dense index of the current thread, stable for the thread's
+/// lifetime. Used to spread threads over per-thread slots.
+pub(crate) fn thread_index() -> usize {
+    static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static INDEX: usize = NEXT_INDEX.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| *index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::thread;
+
+    pub(super) const THREADS: usize = 4;
+    pub(super) const INCREMENTS: u64 = 10_000;
+
+    /// Increments one `C` from `THREADS` threads at once and checks that
+    /// none of the increments got lost.
+    fn increment_concurrently<C: CounterTrait>() {
+        let counter = C::new(0, Hold::None);
+        let threads = (0..THREADS)
+            .map(|_| {
+                let counter = counter.clone();
+                thread::spawn(move || {
+                    for _ in 0..INCREMENTS {
+                        block_on(counter.increment());
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(
+            block_on(counter.get()).value,
+            THREADS as u64 * INCREMENTS,
+            "{} lost increments",
+            C::name()
+        );
+    }
+
+    #[test]
+    fn std_rwlock_keeps_every_increment() {
+        increment_concurrently::<Counter>();
+    }
+
+    #[test]
+    fn async_std_rwlock_keeps_every_increment() {
+        increment_concurrently::<AsyncCounter>();
+    }
+
+    #[test]
+    fn parking_lot_rwlock_keeps_every_increment() {
+        increment_concurrently::<ParkingLotRwLock>();
+    }
+
+    #[test]
+    fn parking_lot_mutex_keeps_every_increment() {
+        increment_concurrently::<ParkingLotMutex>();
+    }
+
+    #[test]
+    fn parking_lot_fair_mutex_keeps_every_increment() {
+        increment_concurrently::<ParkingLotFairMutex>();
+    }
+
+    #[test]
+    fn tokio_rwlock_keeps_every_increment() {
+        increment_concurrently::<TokioRwLock>();
+    }
+
+    #[test]
+    fn tokio_mutex_keeps_every_increment() {
+        increment_concurrently::<TokioMutex>();
+    }
+
+    #[test]
+    fn tokio_semaphore_keeps_every_increment() {
+        increment_concurrently::<TokioSemaphore>();
+    }
+
+    #[test]
+    fn relaxed_atomic_keeps_every_increment() {
+        increment_concurrently::<AtomicCounter<Relaxed>>();
+    }
+
+    #[test]
+    fn acquire_release_atomic_keeps_every_increment() {
+        increment_concurrently::<AtomicCounter<AcquireRelease>>();
+    }
+
+    #[test]
+    fn seq_cst_atomic_keeps_every_increment() {
+        increment_concurrently::<AtomicCounter<SeqCst>>();
+    }
+
+    #[test]
+    fn seqlock_keeps_every_increment() {
+        increment_concurrently::<SeqLockCounter>();
+    }
+
+    #[test]
+    fn sharded_keeps_every_increment() {
+        increment_concurrently::<ShardedCounter>();
+    }
+
+    #[test]
+    fn rcu_keeps_every_increment() {
+        increment_concurrently::<RcuCounter>();
+    }
+
+    #[test]
+    fn crossbeam_sharded_lock_keeps_every_increment() {
+        increment_concurrently::<ShardedLockCounter>();
+    }
+
+    #[test]
+    fn left_right_keeps_every_increment() {
+        increment_concurrently::<LeftRightCounter>();
+    }
+
+    #[test]
+    fn tas_lock_keeps_every_increment() {
+        increment_concurrently::<SpinCounter<TasLock>>();
+    }
+
+    #[test]
+    fn ttas_lock_keeps_every_increment() {
+        increment_concurrently::<SpinCounter<TtasLock>>();
+    }
+
+    #[test]
+    fn ticket_lock_keeps_every_increment() {
+        increment_concurrently::<SpinCounter<TicketLock>>();
+    }
+
+    #[test]
+    fn mcs_lock_keeps_every_increment() {
+        increment_concurrently::<SpinCounter<McsLock>>();
+    }
+
+    #[test]
+    fn flat_combining_keeps_every_increment() {
+        increment_concurrently::<FlatCombiningCounter>();
+    }
+}
@@ -0,0 +1,259 @@
+use super::{swap_if_equal, CounterTrait};
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use async_trait::async_trait;
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use std::{sync::Arc, time::Instant};
+
+pub struct ParkingLotRwLock<P = u64> {
+    inner: Arc<RwLock<P>>,
+    hold: Hold,
+}
+
+impl<P: Payload> ParkingLotRwLock<P> {
+    fn new(initial: P, hold: Hold) -> Self {
+        ParkingLotRwLock {
+            inner: Arc::new(RwLock::new(initial)),
+            hold,
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let val = self.inner.read();
+        let acquired = Instant::now();
+        Timed::new(val.clone(), acquired)
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let mut curr = self.inner.write();
+        let acquired = Instant::now();
+        self.hold.block();
+        Timed::new(f(&mut *curr), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment)
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new))
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr))
+    }
+}
+
+impl<P> Clone for ParkingLotRwLock<P> {
+    fn clone(&self) -> ParkingLotRwLock<P> {
+        ParkingLotRwLock {
+            inner: Arc::clone(&self.inner),
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for ParkingLotRwLock<P> {
+    fn name() -> &'static str {
+        "ParkingLotRwLock"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        ParkingLotRwLock::new(initial, hold)
+    }
+
+    fn supports_hold(hold: Hold) -> bool {
+        hold.is_sync()
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+/// `parking_lot::Mutex`, optionally handing the lock straight to the next
+/// waiter on unlock (`unlock_fair`) instead of letting the unlocking thread
+/// barge back in.
+pub struct ParkingLotMutex<P = u64> {
+    inner: Arc<Mutex<P>>,
+    fair: bool,
+    hold: Hold,
+}
+
+impl<P: Payload> ParkingLotMutex<P> {
+    fn new(initial: P, fair: bool, hold: Hold) -> Self {
+        ParkingLotMutex {
+            inner: Arc::new(Mutex::new(initial)),
+            fair,
+            hold,
+        }
+    }
+
+    fn unlock(&self, guard: MutexGuard<P>) {
+        if self.fair {
+            MutexGuard::unlock_fair(guard);
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let guard = self.inner.lock();
+        let acquired = Instant::now();
+        let timed = Timed::new(guard.clone(), acquired);
+        self.unlock(guard);
+        timed
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let mut guard = self.inner.lock();
+        let acquired = Instant::now();
+        self.hold.block();
+        let result = f(&mut *guard);
+        self.unlock(guard);
+        Timed::new(result, acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment)
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new))
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr))
+    }
+}
+
+impl<P> Clone for ParkingLotMutex<P> {
+    fn clone(&self) -> ParkingLotMutex<P> {
+        ParkingLotMutex {
+            inner: Arc::clone(&self.inner),
+            fair: self.fair,
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for ParkingLotMutex<P> {
+    fn name() -> &'static str {
+        "ParkingLotMutex"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        ParkingLotMutex::new(initial, false, hold)
+    }
+
+    fn supports_hold(hold: Hold) -> bool {
+        hold.is_sync()
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub struct ParkingLotFairMutex<P = u64> {
+    inner: ParkingLotMutex<P>,
+}
+
+impl<P> Clone for ParkingLotFairMutex<P> {
+    fn clone(&self) -> ParkingLotFairMutex<P> {
+        ParkingLotFairMutex {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for ParkingLotFairMutex<P> {
+    fn name() -> &'static str {
+        "ParkingLotFairMutex"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        ParkingLotFairMutex {
+            inner: ParkingLotMutex::new(initial, true, hold),
+        }
+    }
+
+    fn supports_hold(hold: Hold) -> bool {
+        hold.is_sync()
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.inner.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.inner.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.inner.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.inner.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.inner.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    register_payloads!(registry, ParkingLotRwLock);
+    register_payloads!(registry, ParkingLotMutex);
+    register_payloads!(registry, ParkingLotFairMutex);
+}
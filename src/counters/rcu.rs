@@ -0,0 +1,167 @@
+use super::CounterTrait;
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+struct RcuState<P> {
+    current: ArcSwap<P>,
+    /// Snapshots replaced by writers.
+    retired: AtomicU64,
+    /// Retired snapshots still referenced by a reader, freed by the last one.
+    deferred: AtomicU64,
+    /// Time writers spent releasing retired snapshots, reported as
+    /// `reclaim_ns`. Only the writer's own release is timed: a deferred
+    /// snapshot is freed when its last reader drops its guard, and that free
+    /// is not counted, so the metric is a lower bound whenever
+    /// `deferred_reclaims` is non-zero.
+    reclaim_nanos: AtomicU64,
+}
+
+/// Read-copy-update: readers load the current `Arc` snapshot without
+/// blocking, writers publish a fresh snapshot and leave the old one to be
+/// reclaimed once its last reader is done with it.
+pub struct RcuCounter<P = u64> {
+    inner: Arc<RcuState<P>>,
+}
+
+impl<P: Payload> RcuCounter<P> {
+    fn new(initial: P) -> Self {
+        RcuCounter {
+            inner: Arc::new(RcuState {
+                current: ArcSwap::from_pointee(initial),
+                retired: AtomicU64::new(0),
+                deferred: AtomicU64::new(0),
+                reclaim_nanos: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let acquired = Instant::now();
+        let snapshot = self.inner.current.load();
+        Timed::new((**snapshot).clone(), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        let acquired = Instant::now();
+        let old = self.inner.current.swap(Arc::new(val));
+        self.retire(old);
+        Timed::new((), acquired)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.update(|current| {
+            let mut next = current.clone();
+            next.increment();
+            next
+        })
+        .await
+    }
+
+    /// Publishes `new` only if the current snapshot equals `current`. A
+    /// failed comparison republishes the snapshot it was made against, which
+    /// is a no-op unless another writer got in first, then `rcu` retries.
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        let acquired = Instant::now();
+        let mut swapped = false;
+        let old = self.inner.current.rcu(|snapshot| {
+            swapped = **snapshot == current;
+            if swapped {
+                Arc::new(new.clone())
+            } else {
+                Arc::clone(snapshot)
+            }
+        });
+        let result = if swapped {
+            self.retire(old);
+            Ok(())
+        } else {
+            Err((*old).clone())
+        };
+        Timed::new(result, acquired)
+    }
+
+    /// Copy, modify and publish, retrying with a fresh copy when another
+    /// writer published in between.
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        let acquired = Instant::now();
+        let old = self.inner.current.rcu(|snapshot| f(&**snapshot));
+        self.retire(old);
+        Timed::new((), acquired)
+    }
+
+    fn retire(&self, old: Arc<P>) {
+        let state = &self.inner;
+        state.retired.fetch_add(1, Ordering::Relaxed);
+        if Arc::strong_count(&old) > 1 {
+            state.deferred.fetch_add(1, Ordering::Relaxed);
+        }
+        let start = Instant::now();
+        drop(old);
+        state
+            .reclaim_nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl<P> Clone for RcuCounter<P> {
+    fn clone(&self) -> RcuCounter<P> {
+        RcuCounter {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for RcuCounter<P> {
+    fn name() -> &'static str {
+        "Rcu"
+    }
+
+    fn new(initial: P, _: Hold) -> Self {
+        RcuCounter::new(initial)
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+
+    fn metrics(&self) -> Vec<(&'static str, u64)> {
+        let state = &self.inner;
+        vec![
+            ("retired", state.retired.load(Ordering::Relaxed)),
+            ("deferred_reclaims", state.deferred.load(Ordering::Relaxed)),
+            ("reclaim_ns", state.reclaim_nanos.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    register_payloads!(registry, RcuCounter);
+}
@@ -0,0 +1,152 @@
+use super::{swap_if_equal, CounterTrait};
+use crate::{hold::Hold, latency::Timed, registry::Registry};
+use async_trait::async_trait;
+use std::{
+    sync::{
+        atomic::{self, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+struct SeqLockState {
+    /// Odd while a writer is inside the critical section.
+    sequence: AtomicU64,
+    value: AtomicU64,
+    read_retries: AtomicU64,
+}
+
+/// Sequence lock: readers never block and instead retry when a writer bumped
+/// the sequence while they were reading. Writers exclude each other by
+/// moving the sequence from even to odd.
+pub struct SeqLockCounter {
+    inner: Arc<SeqLockState>,
+}
+
+impl SeqLockCounter {
+    fn new(initial: u64) -> Self {
+        SeqLockCounter {
+            inner: Arc::new(SeqLockState {
+                sequence: AtomicU64::new(0),
+                value: AtomicU64::new(initial),
+                read_retries: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        let state = &self.inner;
+        loop {
+            let before = state.sequence.load(Ordering::Acquire);
+            if before & 1 == 0 {
+                let val = state.value.load(Ordering::Relaxed);
+                atomic::fence(Ordering::Acquire);
+                if state.sequence.load(Ordering::Relaxed) == before {
+                    return Timed::new(val, Instant::now());
+                }
+            }
+            state.read_retries.fetch_add(1, Ordering::Relaxed);
+            atomic::spin_loop_hint();
+        }
+    }
+
+    /// Runs `f` on the value with the sequence odd, so readers retry until
+    /// the new value is in place.
+    fn write<R>(&self, f: impl FnOnce(&mut u64) -> R) -> Timed<R> {
+        let state = &self.inner;
+        let mut sequence = state.sequence.load(Ordering::Relaxed);
+        loop {
+            if sequence & 1 == 0 {
+                match state.sequence.compare_exchange_weak(
+                    sequence,
+                    sequence + 1,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    Err(current) => sequence = current,
+                }
+            } else {
+                atomic::spin_loop_hint();
+                sequence = state.sequence.load(Ordering::Relaxed);
+            }
+        }
+        let acquired = Instant::now();
+        atomic::fence(Ordering::Release);
+        // Writers exclude each other, the value only changes under us here.
+        let mut val = state.value.load(Ordering::Relaxed);
+        let result = f(&mut val);
+        state.value.store(val, Ordering::Relaxed);
+        state.sequence.store(sequence + 2, Ordering::Release);
+        Timed::new(result, acquired)
+    }
+
+    async fn set(&self, val: u64) -> Timed<()> {
+        self.write(|curr| *curr = val)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(|curr| *curr = curr.wrapping_add(1))
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        self.write(|curr| swap_if_equal(curr, current, new))
+    }
+
+    async fn update(&self, f: impl Fn(&u64) -> u64) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr))
+    }
+}
+
+impl Clone for SeqLockCounter {
+    fn clone(&self) -> SeqLockCounter {
+        SeqLockCounter {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[async_trait]
+impl CounterTrait for SeqLockCounter {
+    fn name() -> &'static str {
+        "SeqLock"
+    }
+
+    fn new(initial: u64, _: Hold) -> Self {
+        SeqLockCounter::new(initial)
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        self.get().await
+    }
+
+    async fn set(&self, value: u64) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&u64) -> u64 + Send + Sync,
+    {
+        self.update(f).await
+    }
+
+    fn metrics(&self) -> Vec<(&'static str, u64)> {
+        vec![(
+            "read_retries",
+            self.inner.read_retries.load(Ordering::Relaxed),
+        )]
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    registry.register::<u64, SeqLockCounter>();
+}
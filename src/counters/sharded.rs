@@ -0,0 +1,135 @@
+use super::{swap_if_equal, thread_index, CounterTrait};
+use crate::{hold::Hold, latency::Timed, registry::Registry};
+use async_trait::async_trait;
+use crossbeam::utils::CachePadded;
+use std::{
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+/// Counter split into cache-line padded shards, each behind its own
+/// `RwLock`. A `set` only overwrites the shard of the calling worker thread
+/// and `get` returns the sum over all shards, the usual trade for hot
+/// counters: cheap uncontended writes, reads that touch every shard. The
+/// number of shards comes from the workload, one per core by default.
+pub struct ShardedCounter {
+    shards: Arc<Vec<CachePadded<RwLock<u64>>>>,
+}
+
+impl ShardedCounter {
+    fn new(initial: u64, shards: usize) -> Self {
+        // The value is the sum of all shards, so the first one starts at it.
+        let shards = (0..shards)
+            .map(|shard| CachePadded::new(RwLock::new(if shard == 0 { initial } else { 0 })))
+            .collect();
+        ShardedCounter {
+            shards: Arc::new(shards),
+        }
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        let mut sum = 0u64;
+        for shard in self.shards.iter() {
+            sum = sum.wrapping_add(*shard.read().unwrap());
+        }
+        Timed::new(sum, Instant::now())
+    }
+
+    fn write_own<R>(&self, f: impl FnOnce(&mut u64) -> R) -> Timed<R> {
+        let shard = &self.shards[thread_index() % self.shards.len()];
+        let mut curr = shard.write().unwrap();
+        let acquired = Instant::now();
+        Timed::new(f(&mut *curr), acquired)
+    }
+
+    /// Locks every shard in index order, so that `f` sees and replaces the
+    /// whole sum. The result is folded into the first shard.
+    fn write_all<R>(&self, f: impl FnOnce(&mut u64) -> R) -> Timed<R> {
+        let mut guards = self
+            .shards
+            .iter()
+            .map(|shard| shard.write().unwrap())
+            .collect::<Vec<_>>();
+        let acquired = Instant::now();
+        let mut sum = guards
+            .iter()
+            .fold(0u64, |sum, guard| sum.wrapping_add(**guard));
+        let result = f(&mut sum);
+        for guard in &mut guards {
+            **guard = 0;
+        }
+        *guards[0] = sum;
+        Timed::new(result, acquired)
+    }
+
+    async fn set(&self, val: u64) -> Timed<()> {
+        self.write_own(|curr| *curr = val)
+    }
+
+    /// Only touches the caller's shard, the case sharding is made for.
+    async fn increment(&self) -> Timed<()> {
+        self.write_own(|curr| *curr = curr.wrapping_add(1))
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        self.write_all(|sum| swap_if_equal(sum, current, new))
+    }
+
+    async fn update(&self, f: impl Fn(&u64) -> u64) -> Timed<()> {
+        self.write_all(|sum| *sum = f(&*sum))
+    }
+}
+
+impl Clone for ShardedCounter {
+    fn clone(&self) -> ShardedCounter {
+        ShardedCounter {
+            shards: Arc::clone(&self.shards),
+        }
+    }
+}
+
+#[async_trait]
+impl CounterTrait for ShardedCounter {
+    fn name() -> &'static str {
+        "Sharded"
+    }
+
+    fn new(initial: u64, _: Hold) -> Self {
+        ShardedCounter::new(initial, num_cpus::get())
+    }
+
+    fn with_shards(initial: u64, _: Hold, shards: usize) -> Self {
+        ShardedCounter::new(initial, shards)
+    }
+
+    fn uses_shards() -> bool {
+        true
+    }
+
+    async fn get(&self) -> Timed<u64> {
+        self.get().await
+    }
+
+    async fn set(&self, value: u64) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: u64, new: u64) -> Timed<Result<(), u64>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&u64) -> u64 + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    registry.register::<u64, ShardedCounter>();
+}
@@ -0,0 +1,101 @@
+use super::{swap_if_equal, CounterTrait};
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use async_trait::async_trait;
+use crossbeam::sync::ShardedLock;
+use std::{sync::Arc, time::Instant};
+
+/// `crossbeam::sync::ShardedLock`: readers lock only their own shard,
+/// writers lock all of them.
+pub struct ShardedLockCounter<P = u64> {
+    inner: Arc<ShardedLock<P>>,
+    hold: Hold,
+}
+
+impl<P: Payload> ShardedLockCounter<P> {
+    fn new(initial: P, hold: Hold) -> Self {
+        ShardedLockCounter {
+            inner: Arc::new(ShardedLock::new(initial)),
+            hold,
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let val = self.inner.read().unwrap();
+        let acquired = Instant::now();
+        Timed::new(val.clone(), acquired)
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let mut curr = self.inner.write().unwrap();
+        let acquired = Instant::now();
+        self.hold.block();
+        Timed::new(f(&mut *curr), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment)
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new))
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr))
+    }
+}
+
+impl<P> Clone for ShardedLockCounter<P> {
+    fn clone(&self) -> ShardedLockCounter<P> {
+        ShardedLockCounter {
+            inner: Arc::clone(&self.inner),
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for ShardedLockCounter<P> {
+    fn name() -> &'static str {
+        "CrossbeamShardedLock"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        ShardedLockCounter::new(initial, hold)
+    }
+
+    fn supports_hold(hold: Hold) -> bool {
+        hold.is_sync()
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    register_payloads!(registry, ShardedLockCounter);
+}
@@ -0,0 +1,324 @@
+//! Classic spinlocks wrapped as counters.
+//!
+//! None of these locks know about the executor: a task that finds the lock
+//! taken keeps its tokio worker thread busy until the lock is released, it
+//! never yields back to the scheduler. That is harmless as long as the
+//! critical section is short and never awaits, which holds for the counters
+//! below, because the holder then always runs on another OS thread and
+//! releases the lock soon. It goes wrong when the holder gets descheduled
+//! by the OS: every waiter burns its worker until the holder runs again, and
+//! with the FIFO locks (ticket, MCS) a descheduled *waiter* stalls everyone
+//! queued behind it as well. On a single-threaded executor the locks never
+//! see contention, since one task runs its critical section to completion
+//! before the next task is polled. Holding any of these locks across an
+//! `.await` would deadlock a single-threaded executor and can exhaust the
+//! workers of a multi-threaded one.
+
+use super::{swap_if_equal, CounterTrait};
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use async_trait::async_trait;
+use std::{
+    cell::UnsafeCell,
+    ptr,
+    sync::{
+        atomic::{self, AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+pub trait RawSpinLock: Default + Send + Sync + 'static {
+    const NAME: &'static str;
+
+    /// Per-acquisition state owned by the caller, e.g. the MCS queue node.
+    /// Lives on the caller's stack, so acquiring never allocates.
+    type Node: Default;
+
+    /// # Safety
+    ///
+    /// `node` must stay in place and must not be used for another
+    /// acquisition until the matching `unlock`.
+    unsafe fn lock(&self, node: &Self::Node);
+
+    /// # Safety
+    ///
+    /// The lock must be held through `node`.
+    unsafe fn unlock(&self, node: &Self::Node);
+}
+
+/// Test-and-set: every attempt is an atomic swap, so waiters keep the cache
+/// line bouncing between cores.
+#[derive(Default)]
+pub struct TasLock {
+    locked: AtomicBool,
+}
+
+impl RawSpinLock for TasLock {
+    const NAME: &'static str = "SpinTas";
+    type Node = ();
+
+    unsafe fn lock(&self, _: &()) {
+        while self.locked.swap(true, Ordering::Acquire) {
+            atomic::spin_loop_hint();
+        }
+    }
+
+    unsafe fn unlock(&self, _: &()) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+/// Test-and-test-and-set: waiters spin on a plain load of their cached copy
+/// and only swap once the lock looks free, backing off exponentially after
+/// each lost race.
+#[derive(Default)]
+pub struct TtasLock {
+    locked: AtomicBool,
+}
+
+const MAX_BACKOFF_SPINS: u32 = 1 << 10;
+
+impl RawSpinLock for TtasLock {
+    const NAME: &'static str = "SpinTtas";
+    type Node = ();
+
+    unsafe fn lock(&self, _: &()) {
+        let mut backoff = 1;
+        loop {
+            while self.locked.load(Ordering::Relaxed) {
+                atomic::spin_loop_hint();
+            }
+            if !self.locked.swap(true, Ordering::Acquire) {
+                return;
+            }
+            for _ in 0..backoff {
+                atomic::spin_loop_hint();
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF_SPINS);
+        }
+    }
+
+    unsafe fn unlock(&self, _: &()) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+/// Ticket lock: waiters are served in arrival order.
+#[derive(Default)]
+pub struct TicketLock {
+    next_ticket: AtomicUsize,
+    now_serving: AtomicUsize,
+}
+
+impl RawSpinLock for TicketLock {
+    const NAME: &'static str = "SpinTicket";
+    type Node = ();
+
+    unsafe fn lock(&self, _: &()) {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        while self.now_serving.load(Ordering::Acquire) != ticket {
+            atomic::spin_loop_hint();
+        }
+    }
+
+    unsafe fn unlock(&self, _: &()) {
+        // Only the holder writes `now_serving`, a plain increment is enough.
+        let serving = self.now_serving.load(Ordering::Relaxed);
+        self.now_serving
+            .store(serving.wrapping_add(1), Ordering::Release);
+    }
+}
+
+#[derive(Default)]
+pub struct McsNode {
+    next: AtomicPtr<McsNode>,
+    locked: AtomicBool,
+}
+
+/// MCS queue lock: FIFO like the ticket lock, but every waiter spins on a
+/// flag in its own queue node instead of a shared word.
+pub struct McsLock {
+    tail: AtomicPtr<McsNode>,
+}
+
+impl Default for McsLock {
+    fn default() -> Self {
+        McsLock {
+            tail: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+impl RawSpinLock for McsLock {
+    const NAME: &'static str = "SpinMcs";
+    type Node = McsNode;
+
+    unsafe fn lock(&self, node: &McsNode) {
+        node.next.store(ptr::null_mut(), Ordering::Relaxed);
+        node.locked.store(true, Ordering::Relaxed);
+        // The node is only ever accessed through its atomics.
+        let node_ptr = node as *const McsNode as *mut McsNode;
+        let previous = self.tail.swap(node_ptr, Ordering::AcqRel);
+        if !previous.is_null() {
+            (*previous).next.store(node_ptr, Ordering::Release);
+            while node.locked.load(Ordering::Acquire) {
+                atomic::spin_loop_hint();
+            }
+        }
+    }
+
+    unsafe fn unlock(&self, node: &McsNode) {
+        let node_ptr = node as *const McsNode as *mut McsNode;
+        let mut next = node.next.load(Ordering::Acquire);
+        if next.is_null() {
+            if self
+                .tail
+                .compare_exchange(
+                    node_ptr,
+                    ptr::null_mut(),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                return;
+            }
+            // A successor swapped itself into the tail but has not linked
+            // itself to us yet.
+            loop {
+                next = node.next.load(Ordering::Acquire);
+                if !next.is_null() {
+                    break;
+                }
+                atomic::spin_loop_hint();
+            }
+        }
+        // Once released the successor no longer touches our node, so it
+        // can go out of scope with the caller's frame.
+        (*next).locked.store(false, Ordering::Release);
+    }
+}
+
+struct SpinState<L, P> {
+    lock: L,
+    value: UnsafeCell<P>,
+}
+
+// `value` is only accessed while `lock` is held.
+unsafe impl<L: RawSpinLock, P: Send> Sync for SpinState<L, P> {}
+
+impl<L: RawSpinLock, P> SpinState<L, P> {
+    /// Runs `f` on the value under the lock, returns its result and the
+    /// acquisition instant. Synchronous on purpose: the lock is never held
+    /// across an await point.
+    fn with_lock<R>(&self, f: impl FnOnce(&mut P) -> R) -> (R, Instant) {
+        // Stays put on this frame until unlocked below.
+        let node = L::Node::default();
+        unsafe { self.lock.lock(&node) };
+        let acquired = Instant::now();
+        let result = f(unsafe { &mut *self.value.get() });
+        unsafe { self.lock.unlock(&node) };
+        (result, acquired)
+    }
+}
+
+pub struct SpinCounter<L, P = u64> {
+    inner: Arc<SpinState<L, P>>,
+    hold: Hold,
+}
+
+impl<L: RawSpinLock, P: Payload> SpinCounter<L, P> {
+    fn new(initial: P, hold: Hold) -> Self {
+        SpinCounter {
+            inner: Arc::new(SpinState {
+                lock: L::default(),
+                value: UnsafeCell::new(initial),
+            }),
+            hold,
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let (val, acquired) = self.inner.with_lock(|val| val.clone());
+        Timed::new(val, acquired)
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let hold = self.hold;
+        let (result, acquired) = self.inner.with_lock(|curr| {
+            hold.block();
+            f(curr)
+        });
+        Timed::new(result, acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val)
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment)
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new))
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr))
+    }
+}
+
+impl<L, P> Clone for SpinCounter<L, P> {
+    fn clone(&self) -> SpinCounter<L, P> {
+        SpinCounter {
+            inner: Arc::clone(&self.inner),
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<L: RawSpinLock, P: Payload> CounterTrait<P> for SpinCounter<L, P> {
+    fn name() -> &'static str {
+        L::NAME
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        SpinCounter::new(initial, hold)
+    }
+
+    fn supports_hold(hold: Hold) -> bool {
+        hold.is_sync()
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    register_payloads!(registry, SpinCounter<TasLock>);
+    register_payloads!(registry, SpinCounter<TtasLock>);
+    register_payloads!(registry, SpinCounter<TicketLock>);
+    register_payloads!(registry, SpinCounter<McsLock>);
+}
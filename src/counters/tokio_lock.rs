@@ -0,0 +1,305 @@
+use super::{swap_if_equal, CounterTrait};
+use crate::{hold::Hold, latency::Timed, payload::Payload, registry::Registry};
+use async_trait::async_trait;
+use std::{cell::UnsafeCell, sync::Arc, time::Instant};
+use tokio::sync::{Mutex, RwLock, Semaphore};
+
+pub struct TokioRwLock<P = u64> {
+    inner: Arc<RwLock<P>>,
+    hold: Hold,
+}
+
+impl<P: Payload> TokioRwLock<P> {
+    fn new(initial: P, hold: Hold) -> Self {
+        TokioRwLock {
+            inner: Arc::new(RwLock::new(initial)),
+            hold,
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let val = self.inner.read().await;
+        let acquired = Instant::now();
+        Timed::new(val.clone(), acquired)
+    }
+
+    async fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let mut curr = self.inner.write().await;
+        let acquired = Instant::now();
+        self.hold.wait().await;
+        Timed::new(f(&mut *curr), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment).await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new)).await
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr)).await
+    }
+}
+
+impl<P> Clone for TokioRwLock<P> {
+    fn clone(&self) -> TokioRwLock<P> {
+        TokioRwLock {
+            inner: Arc::clone(&self.inner),
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for TokioRwLock<P> {
+    fn name() -> &'static str {
+        "TokioRwLock"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        TokioRwLock::new(initial, hold)
+    }
+
+    fn supports_hold(_: Hold) -> bool {
+        true
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub struct TokioMutex<P = u64> {
+    inner: Arc<Mutex<P>>,
+    hold: Hold,
+}
+
+impl<P: Payload> TokioMutex<P> {
+    fn new(initial: P, hold: Hold) -> Self {
+        TokioMutex {
+            inner: Arc::new(Mutex::new(initial)),
+            hold,
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let val = self.inner.lock().await;
+        let acquired = Instant::now();
+        Timed::new(val.clone(), acquired)
+    }
+
+    async fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let mut curr = self.inner.lock().await;
+        let acquired = Instant::now();
+        self.hold.wait().await;
+        Timed::new(f(&mut *curr), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment).await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new)).await
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr)).await
+    }
+}
+
+impl<P> Clone for TokioMutex<P> {
+    fn clone(&self) -> TokioMutex<P> {
+        TokioMutex {
+            inner: Arc::clone(&self.inner),
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for TokioMutex<P> {
+    fn name() -> &'static str {
+        "TokioMutex"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        TokioMutex::new(initial, hold)
+    }
+
+    fn supports_hold(_: Hold) -> bool {
+        true
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+/// Readers take one permit each, so up to `MAX_READERS` of them share the
+/// value. Writers take every permit one by one; the writer mutex keeps two
+/// writers from deadlocking on half of the permits each.
+const MAX_READERS: usize = 32;
+
+struct SemaphoreState<P> {
+    permits: Semaphore,
+    writer: Mutex<()>,
+    value: UnsafeCell<P>,
+}
+
+// Access to `value` is guarded by the permits: shared with one permit,
+// exclusive with all of them.
+unsafe impl<P: Send + Sync> Sync for SemaphoreState<P> {}
+
+pub struct TokioSemaphore<P = u64> {
+    inner: Arc<SemaphoreState<P>>,
+    hold: Hold,
+}
+
+impl<P: Payload> TokioSemaphore<P> {
+    fn new(initial: P, hold: Hold) -> Self {
+        TokioSemaphore {
+            inner: Arc::new(SemaphoreState {
+                permits: Semaphore::new(MAX_READERS),
+                writer: Mutex::new(()),
+                value: UnsafeCell::new(initial),
+            }),
+            hold,
+        }
+    }
+
+    async fn get(&self) -> Timed<P> {
+        let _permit = self.inner.permits.acquire().await;
+        let acquired = Instant::now();
+        let val = unsafe { (*self.inner.value.get()).clone() };
+        Timed::new(val, acquired)
+    }
+
+    async fn write<R>(&self, f: impl FnOnce(&mut P) -> R) -> Timed<R> {
+        let _writer = self.inner.writer.lock().await;
+        let mut permits = Vec::with_capacity(MAX_READERS);
+        for _ in 0..MAX_READERS {
+            permits.push(self.inner.permits.acquire().await);
+        }
+        let acquired = Instant::now();
+        self.hold.wait().await;
+        Timed::new(f(unsafe { &mut *self.inner.value.get() }), acquired)
+    }
+
+    async fn set(&self, val: P) -> Timed<()> {
+        self.write(|curr| *curr = val).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.write(P::increment).await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.write(|curr| swap_if_equal(curr, current, new)).await
+    }
+
+    async fn update(&self, f: impl Fn(&P) -> P) -> Timed<()> {
+        self.write(|curr| *curr = f(&*curr)).await
+    }
+}
+
+impl<P> Clone for TokioSemaphore<P> {
+    fn clone(&self) -> TokioSemaphore<P> {
+        TokioSemaphore {
+            inner: Arc::clone(&self.inner),
+            hold: self.hold,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Payload> CounterTrait<P> for TokioSemaphore<P> {
+    fn name() -> &'static str {
+        "TokioSemaphore"
+    }
+
+    fn new(initial: P, hold: Hold) -> Self {
+        TokioSemaphore::new(initial, hold)
+    }
+
+    fn supports_hold(_: Hold) -> bool {
+        true
+    }
+
+    async fn get(&self) -> Timed<P> {
+        self.get().await
+    }
+
+    async fn set(&self, value: P) -> Timed<()> {
+        self.set(value).await
+    }
+
+    async fn increment(&self) -> Timed<()> {
+        self.increment().await
+    }
+
+    async fn compare_and_swap(&self, current: P, new: P) -> Timed<Result<(), P>> {
+        self.compare_and_swap(current, new).await
+    }
+
+    async fn update<F>(&self, f: F) -> Timed<()>
+    where
+        F: Fn(&P) -> P + Send + Sync,
+    {
+        self.update(f).await
+    }
+}
+
+pub(crate) fn register(registry: &mut Registry) {
+    register_payloads!(registry, TokioRwLock);
+    register_payloads!(registry, TokioMutex);
+    register_payloads!(registry, TokioSemaphore);
+}
@@ -0,0 +1,245 @@
+use crate::{
+    barrier::StartBarrier,
+    counters::CounterTrait,
+    executor::Executor,
+    hold::Hold,
+    latency::{Latencies, OpKind, Sample},
+    mix::Mix,
+    payload::{Payload, PayloadSpec},
+    registry::Entry,
+    report::Report,
+    watchdog::Watchdog,
+};
+use futures::{stream::FuturesUnordered, StreamExt};
+use rand::{thread_rng, Rng};
+use std::{collections::BTreeMap, time::Instant};
+use structopt::StructOpt;
+
+#[derive(Debug, Clone, Copy, StructOpt)]
+pub struct Trials {
+    /// Runs per scenario whose results are discarded
+    #[structopt(long = "warmup", default_value = "1")]
+    pub warmup: usize,
+
+    /// Measured runs per scenario
+    #[structopt(
+        long = "repetitions",
+        default_value = "5",
+        parse(try_from_str = "parse_count")
+    )]
+    pub repetitions: usize,
+}
+
+/// Workload parameters of one run.
+#[derive(Debug, Clone, Copy)]
+pub struct Workload {
+    /// Number of independent counters.
+    pub max_counters: usize,
+    /// Number of operations spawned per counter.
+    pub per_counter_operations_cnt: usize,
+    /// Probability of an operation being a read.
+    pub read_write_ratio: f64,
+    /// How the remaining operations split into writes, increments, CAS
+    /// loops and updates.
+    pub mix: Mix,
+    /// What writers do while holding the lock.
+    pub hold: Hold,
+    /// Value the counters protect.
+    pub payload: PayloadSpec,
+    /// Parts the value is split into by sharded implementations, the others
+    /// ignore it.
+    pub shards: usize,
+}
+
+/// What a spawned operation hands back: a fingerprint of the value it saw or
+/// wrote, its timestamps and how many CAS attempts failed.
+type Outcome = (u64, Sample, u64);
+
+/// Runs `workload` against `max_counters` fresh instances of `C` holding a
+/// `P`: every counter gets `per_counter_operations_cnt` spawned operations,
+/// each a read with probability `read_write_ratio` and otherwise picked from
+/// `mix`. `P` has to be the payload type of `workload.payload` and `C` has
+/// to support `workload.hold`.
+pub async fn test<P: Payload, C: CounterTrait<P>>(
+    executor: &dyn Executor,
+    workload: Workload,
+) -> Report {
+    let Workload {
+        max_counters,
+        per_counter_operations_cnt,
+        read_write_ratio,
+        mix,
+        hold,
+        payload,
+        shards,
+    } = workload;
+    let mut rng = thread_rng();
+    let mut counters = Vec::new();
+    for _ in 0..max_counters {
+        counters.push(C::with_shards(P::generate(payload.size(), 0), hold, shards));
+    }
+
+    // Tasks are spawned up front but park on the gate, so that the clock only
+    // covers the lock operations and not the spawning.
+    let (barrier, gate) = StartBarrier::new(max_counters * per_counter_operations_cnt);
+    let mut futures = FuturesUnordered::new();
+    for index in 0..max_counters {
+        for _ in 0..per_counter_operations_cnt {
+            let counter = counters[index].clone();
+            let gate = gate.clone();
+            let kind = mix.choose(&mut rng, read_write_ratio);
+            let seed = rng.gen_range(0, 10000);
+            let fut = async move {
+                let released = gate.wait().await;
+                // Written values are built after the release so that large
+                // payloads of parked tasks do not pile up in memory.
+                let new_val = if kind == OpKind::Write {
+                    Some(P::generate(payload.size(), seed))
+                } else {
+                    None
+                };
+                let requested = Instant::now();
+                let (digest, acquired, retries) = match kind {
+                    OpKind::Read => {
+                        let timed = counter.get().await;
+                        (timed.value.digest(), timed.acquired, 0)
+                    }
+                    OpKind::Write => {
+                        let new_val = new_val.expect("generated for writes");
+                        let digest = new_val.digest();
+                        (digest, counter.set(new_val).await.acquired, 0)
+                    }
+                    OpKind::Increment => (0, counter.increment().await.acquired, 0),
+                    OpKind::Cas => increment_with_cas(&counter).await,
+                    OpKind::Update => {
+                        let timed = counter
+                            .update(|value: &P| {
+                                let mut next = value.clone();
+                                next.increment();
+                                next
+                            })
+                            .await;
+                        (0, timed.acquired, 0)
+                    }
+                };
+                let sample = Sample {
+                    kind,
+                    released,
+                    requested,
+                    acquired,
+                    completed: Instant::now(),
+                };
+                (digest, sample, retries)
+            };
+            futures.push(executor.spawn_with_handle(fut));
+        }
+    }
+    drop(gate);
+    let watchdog = Watchdog::spawn(executor);
+    let start = barrier.release().await;
+    let results = futures.collect::<Vec<Outcome>>().await;
+    let stalls = watchdog.stop().await;
+    let end = results
+        .iter()
+        .map(|(_, sample, _)| sample.completed)
+        .max()
+        .unwrap_or(start);
+    let elapsed = end.duration_since(start);
+    let mut latencies = Latencies::new();
+    latencies.stall = stalls;
+    let mut cas_retries = 0;
+    for (_, sample, retries) in &results {
+        latencies.record(sample);
+        cas_retries += retries;
+    }
+    let mut metrics = BTreeMap::new();
+    if mix.cas > 0 {
+        metrics.insert("cas_retries", cas_retries);
+    }
+    for counter in &counters {
+        for (name, value) in counter.metrics() {
+            *metrics.entry(name).or_insert(0) += value;
+        }
+    }
+    Report {
+        name: C::name(),
+        max_counters,
+        per_counter_operations_cnt,
+        read_write_ratio,
+        mix,
+        hold,
+        payload,
+        shards: if C::uses_shards() { Some(shards) } else { None },
+        executor: executor.kind(),
+        threads: executor.threads(),
+        elapsed,
+        first_val: results[0].0,
+        latencies,
+        metrics,
+    }
+}
+
+/// Reads the value and tries to swap in its increment until no other write
+/// got in between. Returns the written fingerprint, the acquisition instant
+/// of the successful attempt and the number of failed attempts.
+async fn increment_with_cas<P: Payload, C: CounterTrait<P>>(counter: &C) -> (u64, Instant, u64) {
+    let mut current = counter.get().await.value;
+    let mut retries = 0;
+    loop {
+        let mut next = current.clone();
+        next.increment();
+        let digest = next.digest();
+        let timed = counter.compare_and_swap(current, next).await;
+        match timed.value {
+            Ok(()) => return (digest, timed.acquired, retries),
+            Err(actual) => {
+                retries += 1;
+                current = actual;
+            }
+        }
+    }
+}
+
+/// Runs `trials.warmup` discarded and `trials.repetitions` measured runs of
+/// one scenario on `executor`, blocking until they are done.
+pub fn run_trials(
+    entry: &Entry,
+    executor: &dyn Executor,
+    workload: Workload,
+    trials: Trials,
+) -> Vec<Report> {
+    let mut reports = Vec::with_capacity(trials.repetitions);
+    executor.block_on(Box::pin(async {
+        for _ in 0..trials.warmup {
+            entry.run(executor, workload).await;
+        }
+        for _ in 0..trials.repetitions {
+            reports.push(entry.run(executor, workload).await);
+        }
+    }));
+    reports
+}
+
+pub fn parse_count(s: &str) -> Result<usize, String> {
+    let count = s
+        .parse::<usize>()
+        .map_err(|e| format!("`{}` is not a valid count: {}", s, e))?;
+    validate_count(count)
+}
+
+pub fn validate_count(count: usize) -> Result<usize, String> {
+    if count == 0 {
+        return Err("count must be greater than zero".to_string());
+    }
+    Ok(count)
+}
+
+pub fn validate_ratio(ratio: f64) -> Result<f64, String> {
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(format!(
+            "ratio {} is out of range, expected 0.0..=1.0",
+            ratio
+        ));
+    }
+    Ok(ratio)
+}
@@ -0,0 +1,372 @@
+use futures::{
+    channel::mpsc,
+    executor::{self as futures_executor, LocalPool, ThreadPool},
+    future::{self, BoxFuture, FutureExt, LocalBoxFuture, RemoteHandle},
+    task::SpawnExt,
+    StreamExt,
+};
+use serde::{de, Deserialize, Deserializer};
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+use tokio::runtime::{self, Handle, Runtime};
+
+/// Runtime the operations of a run are spawned on. The driver itself runs
+/// inside `block_on` and only waits for the spawned tasks.
+pub trait Executor {
+    fn kind(&self) -> ExecutorKind;
+
+    /// Number of worker threads running the spawned tasks.
+    fn threads(&self) -> usize;
+
+    /// Spawns `task` onto the executor's workers.
+    fn spawn(&self, task: BoxFuture<'static, ()>);
+
+    /// Drives `future` to completion on the calling thread. Executors
+    /// without worker threads of their own run the spawned tasks here too.
+    fn block_on(&self, future: LocalBoxFuture<'_, ()>);
+}
+
+impl<'e> dyn Executor + 'e {
+    /// Spawns `future` and returns a handle resolving to its output.
+    pub fn spawn_with_handle<F>(&self, future: F) -> RemoteHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send,
+    {
+        let (remote, handle) = future.remote_handle();
+        self.spawn(remote.boxed());
+        handle
+    }
+}
+
+/// Executors a run can be driven by, as spelled in scenarios and on the
+/// command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExecutorKind {
+    TokioMultiThread,
+    TokioCurrentThread,
+    AsyncStd,
+    ThreadPool,
+    ThreadPerCore,
+}
+
+pub const EXECUTOR_KINDS: &[ExecutorKind] = &[
+    ExecutorKind::TokioMultiThread,
+    ExecutorKind::TokioCurrentThread,
+    ExecutorKind::AsyncStd,
+    ExecutorKind::ThreadPool,
+    ExecutorKind::ThreadPerCore,
+];
+
+impl ExecutorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ExecutorKind::TokioMultiThread => "tokio",
+            ExecutorKind::TokioCurrentThread => "tokio-current-thread",
+            ExecutorKind::AsyncStd => "async-std",
+            ExecutorKind::ThreadPool => "thread-pool",
+            ExecutorKind::ThreadPerCore => "thread-per-core",
+        }
+    }
+
+    /// Whether the number of workers can be chosen per executor, which a
+    /// scaling run needs.
+    pub fn scales(self) -> bool {
+        match self {
+            ExecutorKind::TokioCurrentThread | ExecutorKind::AsyncStd => false,
+            _ => true,
+        }
+    }
+
+    /// Checks that the executor can run on `threads` workers. tokio's
+    /// current-thread runtime always has one and async-std's global pool is
+    /// sized once per process.
+    pub fn validate_threads(self, threads: usize) -> Result<(), String> {
+        match self {
+            _ if threads == 0 => Err(format!("{} needs at least one thread", self)),
+            ExecutorKind::TokioCurrentThread if threads != 1 => Err(format!(
+                "{} always runs on one thread, cannot use {}",
+                self, threads
+            )),
+            ExecutorKind::AsyncStd => Err(format!(
+                "{} has a fixed global thread pool, cannot use {} threads",
+                self, threads
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Starts an executor with `threads` workers, or the executor's default
+    /// of one per core when `None`.
+    pub fn build(self, threads: Option<usize>) -> Result<Box<dyn Executor>, String> {
+        if let Some(threads) = threads {
+            self.validate_threads(threads)?;
+        }
+        let threads = threads.unwrap_or_else(num_cpus::get);
+        let executor: Box<dyn Executor> = match self {
+            ExecutorKind::TokioMultiThread => Box::new(TokioMultiThread::new(threads)?),
+            ExecutorKind::TokioCurrentThread => Box::new(TokioCurrentThread::new()?),
+            ExecutorKind::AsyncStd => Box::new(AsyncStd),
+            ExecutorKind::ThreadPool => Box::new(FuturesThreadPool::new(threads)?),
+            ExecutorKind::ThreadPerCore => Box::new(ThreadPerCore::new(threads)),
+        };
+        Ok(executor)
+    }
+}
+
+impl Default for ExecutorKind {
+    fn default() -> Self {
+        ExecutorKind::TokioMultiThread
+    }
+}
+
+impl fmt::Display for ExecutorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ExecutorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EXECUTOR_KINDS
+            .iter()
+            .cloned()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names = EXECUTOR_KINDS
+                    .iter()
+                    .map(|kind| kind.name())
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown executor `{}`, expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for ExecutorKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Tokio's work-stealing thread pool.
+struct TokioMultiThread {
+    runtime: RefCell<Runtime>,
+    spawner: Handle,
+    threads: usize,
+}
+
+impl TokioMultiThread {
+    fn new(threads: usize) -> Result<Self, String> {
+        let runtime = runtime::Builder::new()
+            .threaded_scheduler()
+            .core_threads(threads)
+            .build()
+            .map_err(|e| format!("failed to start the tokio runtime: {}", e))?;
+        let spawner = runtime.handle().clone();
+        Ok(TokioMultiThread {
+            runtime: RefCell::new(runtime),
+            spawner,
+            threads,
+        })
+    }
+}
+
+impl Executor for TokioMultiThread {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::TokioMultiThread
+    }
+
+    fn threads(&self) -> usize {
+        self.threads
+    }
+
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        // Dropping the join handle detaches the task.
+        self.spawner.spawn(task);
+    }
+
+    fn block_on(&self, future: LocalBoxFuture<'_, ()>) {
+        self.runtime.borrow_mut().block_on(future);
+    }
+}
+
+/// Tokio's single-threaded runtime: the spawned tasks share the thread
+/// calling `block_on` with the driver.
+struct TokioCurrentThread {
+    runtime: RefCell<Runtime>,
+    spawner: Handle,
+}
+
+impl TokioCurrentThread {
+    fn new() -> Result<Self, String> {
+        let runtime = runtime::Builder::new()
+            .basic_scheduler()
+            .build()
+            .map_err(|e| format!("failed to start the tokio runtime: {}", e))?;
+        let spawner = runtime.handle().clone();
+        Ok(TokioCurrentThread {
+            runtime: RefCell::new(runtime),
+            spawner,
+        })
+    }
+}
+
+impl Executor for TokioCurrentThread {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::TokioCurrentThread
+    }
+
+    fn threads(&self) -> usize {
+        1
+    }
+
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        self.spawner.spawn(task);
+    }
+
+    fn block_on(&self, future: LocalBoxFuture<'_, ()>) {
+        self.runtime.borrow_mut().block_on(future);
+    }
+}
+
+/// async-std's global executor, shared by every run of the process.
+struct AsyncStd;
+
+impl Executor for AsyncStd {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::AsyncStd
+    }
+
+    /// async-std starts one worker per core.
+    fn threads(&self) -> usize {
+        num_cpus::get()
+    }
+
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        // Dropping the join handle detaches the task.
+        async_std::task::spawn(task);
+    }
+
+    fn block_on(&self, future: LocalBoxFuture<'_, ()>) {
+        futures_executor::block_on(future);
+    }
+}
+
+/// `futures::executor::ThreadPool`, one shared queue without work stealing.
+struct FuturesThreadPool {
+    pool: ThreadPool,
+    threads: usize,
+}
+
+impl FuturesThreadPool {
+    fn new(threads: usize) -> Result<Self, String> {
+        let pool = ThreadPool::builder()
+            .pool_size(threads)
+            .create()
+            .map_err(|e| format!("failed to start the futures thread pool: {}", e))?;
+        Ok(FuturesThreadPool { pool, threads })
+    }
+}
+
+impl Executor for FuturesThreadPool {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::ThreadPool
+    }
+
+    fn threads(&self) -> usize {
+        self.threads
+    }
+
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        SpawnExt::spawn(&mut self.pool.clone(), task).expect("the thread pool is shut down");
+    }
+
+    fn block_on(&self, future: LocalBoxFuture<'_, ()>) {
+        futures_executor::block_on(future);
+    }
+}
+
+/// One single-threaded executor per core, or per requested thread. Tasks
+/// are dealt out round-robin and never move between threads, so a blocked
+/// thread stalls exactly the tasks it owns.
+struct ThreadPerCore {
+    queues: Vec<mpsc::UnboundedSender<BoxFuture<'static, ()>>>,
+    threads: Vec<thread::JoinHandle<()>>,
+    next: AtomicUsize,
+}
+
+impl ThreadPerCore {
+    fn new(cores: usize) -> Self {
+        let mut queues = Vec::with_capacity(cores);
+        let mut threads = Vec::with_capacity(cores);
+        for core in 0..cores {
+            let (queue, tasks) = mpsc::unbounded();
+            let thread = thread::Builder::new()
+                .name(format!("core-{}", core))
+                .spawn(move || run_core(tasks))
+                .expect("failed to spawn an executor thread");
+            queues.push(queue);
+            threads.push(thread);
+        }
+        ThreadPerCore {
+            queues,
+            threads,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Runs every task sent to this core until the queue closes and the tasks
+/// are done.
+fn run_core(tasks: mpsc::UnboundedReceiver<BoxFuture<'static, ()>>) {
+    let mut pool = LocalPool::new();
+    let mut spawner = pool.spawner();
+    pool.run_until(tasks.for_each(move |task| {
+        spawner.spawn(task).expect("the core's pool is shut down");
+        future::ready(())
+    }));
+    pool.run();
+}
+
+impl Executor for ThreadPerCore {
+    fn kind(&self) -> ExecutorKind {
+        ExecutorKind::ThreadPerCore
+    }
+
+    fn threads(&self) -> usize {
+        self.queues.len()
+    }
+
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        let core = self.next.fetch_add(1, Ordering::Relaxed) % self.queues.len();
+        self.queues[core]
+            .unbounded_send(task)
+            .expect("the core's executor thread is gone");
+    }
+
+    fn block_on(&self, future: LocalBoxFuture<'_, ()>) {
+        futures_executor::block_on(future);
+    }
+}
+
+impl Drop for ThreadPerCore {
+    fn drop(&mut self) {
+        self.queues.clear();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
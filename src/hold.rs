@@ -0,0 +1,174 @@
+use serde::{de, Deserialize, Deserializer};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::atomic::spin_loop_hint,
+    task::{Context, Poll},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// What a writer does while holding the lock, on top of modifying the value:
+///
+/// * `none`: nothing, the best case for every lock,
+/// * `spin:<duration>`: busy work on the CPU,
+/// * `yield`: an `.await` that goes back to the executor once,
+/// * `sleep:<duration>`: an `.await` on a timer,
+/// * `block:<duration>`: a blocking sleep of the worker thread.
+///
+/// Awaiting is only possible while holding an async guard, so sync locks
+/// support `none`, `spin` and `block` only. Durations take an `ns`, `us`,
+/// `ms` or `s` suffix, e.g. `spin:500ns` or `sleep:1ms`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hold {
+    None,
+    Spin(Duration),
+    Yield,
+    Sleep(Duration),
+    Block(Duration),
+}
+
+impl Hold {
+    /// Whether the model can run under a sync guard, i.e. without awaiting.
+    pub fn is_sync(self) -> bool {
+        match self {
+            Hold::None | Hold::Spin(_) | Hold::Block(_) => true,
+            Hold::Yield | Hold::Sleep(_) => false,
+        }
+    }
+
+    /// Runs the model inside a sync critical section. Panics for models that
+    /// need to await, check `is_sync` first.
+    pub fn block(self) {
+        match self {
+            Hold::None => {}
+            Hold::Spin(duration) => spin_for(duration),
+            Hold::Block(duration) => thread::sleep(duration),
+            Hold::Yield | Hold::Sleep(_) => {
+                panic!("hold `{}` cannot run under a sync guard", self)
+            }
+        }
+    }
+
+    /// Runs the model inside an async critical section.
+    pub async fn wait(self) {
+        match self {
+            Hold::None => {}
+            Hold::Spin(duration) => spin_for(duration),
+            Hold::Yield => YieldNow { yielded: false }.await,
+            Hold::Sleep(duration) => async_std::task::sleep(duration).await,
+            Hold::Block(duration) => thread::sleep(duration),
+        }
+    }
+}
+
+fn spin_for(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        spin_loop_hint();
+    }
+}
+
+/// Pending once, waking itself right away, so the task goes to the back of
+/// the executor's queue.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+impl Default for Hold {
+    fn default() -> Self {
+        Hold::None
+    }
+}
+
+impl fmt::Display for Hold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Hold::None => write!(f, "none"),
+            Hold::Spin(duration) => write!(f, "spin:{}", DisplayDuration(duration)),
+            Hold::Yield => write!(f, "yield"),
+            Hold::Sleep(duration) => write!(f, "sleep:{}", DisplayDuration(duration)),
+            Hold::Block(duration) => write!(f, "block:{}", DisplayDuration(duration)),
+        }
+    }
+}
+
+/// Duration in the largest unit that keeps it a whole number.
+struct DisplayDuration(Duration);
+
+impl fmt::Display for DisplayDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        for &(unit, per_unit) in &[("s", 1_000_000_000), ("ms", 1_000_000), ("us", 1_000)] {
+            if nanos >= per_unit && nanos % per_unit == 0 {
+                return write!(f, "{}{}", nanos / per_unit, unit);
+            }
+        }
+        write!(f, "{}ns", nanos)
+    }
+}
+
+impl FromStr for Hold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let kind = parts.next().unwrap_or("");
+        let duration = parts.next();
+        match (kind, duration) {
+            ("none", None) => Ok(Hold::None),
+            ("spin", Some(duration)) => parse_duration(duration).map(Hold::Spin),
+            ("yield", None) => Ok(Hold::Yield),
+            ("sleep", Some(duration)) => parse_duration(duration).map(Hold::Sleep),
+            ("block", Some(duration)) => parse_duration(duration).map(Hold::Block),
+            _ => Err(format!(
+                "unknown hold `{}`, expected none, spin:<duration>, yield, \
+                 sleep:<duration> or block:<duration>",
+                s
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Hold {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Parses a duration with an `ns`, `us`, `ms` or `s` suffix.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let digits_end = s
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or_else(|| s.len());
+    let (digits, unit) = s.split_at(digits_end);
+    let value = digits
+        .parse::<u64>()
+        .map_err(|e| format!("`{}` is not a valid duration: {}", s, e))?;
+    match unit {
+        "ns" => Ok(Duration::from_nanos(value)),
+        "us" => Ok(Duration::from_micros(value)),
+        "ms" => Ok(Duration::from_millis(value)),
+        "s" => Ok(Duration::from_secs(value)),
+        _ => Err(format!(
+            "`{}` needs a unit, expected one of ns, us, ms or s",
+            s
+        )),
+    }
+}
@@ -0,0 +1,147 @@
+use hdrhistogram::Histogram;
+use std::time::{Duration, Instant};
+
+pub const PERCENTILES: &[f64] = &[50.0, 90.0, 99.0, 99.9];
+
+/// Value returned by a counter operation together with the moment the lock
+/// was acquired, so the harness can tell queueing apart from the rest.
+pub struct Timed<T> {
+    pub value: T,
+    pub acquired: Instant,
+}
+
+impl<T> Timed<T> {
+    pub fn new(value: T, acquired: Instant) -> Self {
+        Timed { value, acquired }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpKind {
+    Read,
+    Write,
+    Increment,
+    /// Read followed by compare-and-swap attempts until one succeeds.
+    Cas,
+    Update,
+}
+
+impl OpKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            OpKind::Read => "read",
+            OpKind::Write => "write",
+            OpKind::Increment => "increment",
+            OpKind::Cas => "cas",
+            OpKind::Update => "update",
+        }
+    }
+}
+
+/// Timestamps of one spawned operation.
+pub struct Sample {
+    pub kind: OpKind,
+    pub released: Instant,
+    pub requested: Instant,
+    pub acquired: Instant,
+    pub completed: Instant,
+}
+
+/// Latency distributions of one operation type.
+pub struct OpLatency {
+    /// From the start barrier releasing the task to the operation completing.
+    pub total: Histogram<u64>,
+    /// From requesting the lock to acquiring it.
+    pub wait: Histogram<u64>,
+}
+
+impl OpLatency {
+    fn new() -> Self {
+        OpLatency {
+            total: new_histogram(),
+            wait: new_histogram(),
+        }
+    }
+
+    fn record(&mut self, sample: &Sample) {
+        self.total
+            .saturating_record(nanos(sample.completed.duration_since(sample.released)));
+        self.wait
+            .saturating_record(nanos(sample.acquired.duration_since(sample.requested)));
+    }
+}
+
+pub struct Latencies {
+    pub read: OpLatency,
+    pub write: OpLatency,
+    pub increment: OpLatency,
+    pub cas: OpLatency,
+    pub update: OpLatency,
+    /// How late the watchdog probe woke up, i.e. how long the executor had
+    /// no worker free to poll a ready task.
+    pub stall: Histogram<u64>,
+}
+
+impl Latencies {
+    pub fn new() -> Self {
+        Latencies {
+            read: OpLatency::new(),
+            write: OpLatency::new(),
+            increment: OpLatency::new(),
+            cas: OpLatency::new(),
+            update: OpLatency::new(),
+            stall: new_histogram(),
+        }
+    }
+
+    pub fn record(&mut self, sample: &Sample) {
+        match sample.kind {
+            OpKind::Read => self.read.record(sample),
+            OpKind::Write => self.write.record(sample),
+            OpKind::Increment => self.increment.record(sample),
+            OpKind::Cas => self.cas.record(sample),
+            OpKind::Update => self.update.record(sample),
+        }
+    }
+
+    /// Named histograms in reporting order.
+    pub fn histograms(&self) -> Vec<(&'static str, &Histogram<u64>)> {
+        vec![
+            ("read_total", &self.read.total),
+            ("read_wait", &self.read.wait),
+            ("write_total", &self.write.total),
+            ("write_wait", &self.write.wait),
+            ("increment_total", &self.increment.total),
+            ("increment_wait", &self.increment.wait),
+            ("cas_total", &self.cas.total),
+            ("cas_wait", &self.cas.wait),
+            ("update_total", &self.update.total),
+            ("update_wait", &self.update.wait),
+            ("stall", &self.stall),
+        ]
+    }
+}
+
+impl Default for Latencies {
+    fn default() -> Self {
+        Latencies::new()
+    }
+}
+
+/// Three significant digits from one nanosecond up to one hour.
+pub(crate) fn new_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 3_600_000_000_000, 3).unwrap()
+}
+
+pub(crate) fn nanos(duration: Duration) -> u64 {
+    duration.as_nanos() as u64
+}
+
+/// Value at `percentile` in microseconds.
+pub fn percentile_micros(histogram: &Histogram<u64>, percentile: f64) -> f64 {
+    histogram.value_at_percentile(percentile) as f64 / 1000.0
+}
+
+pub fn max_micros(histogram: &Histogram<u64>) -> f64 {
+    histogram.max() as f64 / 1000.0
+}
@@ -0,0 +1,23 @@
+//! Harness comparing sync and async counters under a read/write workload.
+//!
+//! Implement `counters::CounterTrait` for your own primitive and either run
+//! it directly with `driver::test`, or add it to a `registry::Registry` to
+//! select it by name next to the built-in implementations.
+
+mod barrier;
+pub mod baseline;
+pub mod counters;
+pub mod driver;
+pub mod executor;
+pub mod hold;
+pub mod latency;
+pub mod mix;
+pub mod payload;
+pub mod record;
+pub mod registry;
+pub mod report;
+pub mod scale;
+pub mod scenario;
+pub mod stats;
+pub mod sweep;
+mod watchdog;
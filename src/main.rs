@@ -1,194 +1,260 @@
-use async_std::sync::RwLock as AsyncRwLock;
-use async_trait::async_trait;
-use futures::{stream::FuturesUnordered, StreamExt};
-use rand::{thread_rng, Rng};
-use std::{
-    sync::{Arc, RwLock},
-    time::Instant,
+mod cli;
+
+use crate::cli::{BaselineOpts, Command, Opt, RunOpts, ScaleOpts, Selection, SweepOpts};
+use async_learn::{
+    baseline,
+    driver::{run_trials, Workload},
+    executor::{Executor, ExecutorKind},
+    hold::Hold,
+    payload::PayloadSpec,
+    registry::{Entry, Registry},
+    report::{OutputFormat, Reporter},
+    scale::{self, Curves},
+    scenario::Scenario,
+    sweep::{self, Table},
 };
-use tokio::executor::Executor;
+use glob::Pattern;
+use std::{io, path::Path, process};
+use structopt::StructOpt;
 
-struct AsyncCounter {
-    inner: Arc<AsyncRwLock<u64>>,
+/// Resolves the `--impl`/`--exclude` patterns, exiting on a bad selection.
+fn select<'r>(registry: &'r Registry, selection: &Selection) -> Vec<&'r Entry> {
+    registry
+        .select(&selection.include, &selection.exclude)
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        })
 }
 
-impl AsyncCounter {
-    fn new() -> Self {
-        AsyncCounter {
-            inner: Arc::new(AsyncRwLock::new(0)),
+/// Exits unless every payload can run with `hold` on at least one of
+/// `implementations`, so filters that leave nothing to run are reported
+/// instead of producing empty results.
+fn check_supported(implementations: &[&Entry], payloads: &[PayloadSpec], hold: Hold) {
+    for &payload in payloads {
+        if !implementations
+            .iter()
+            .any(|entry| entry.supports(payload) && entry.supports_hold(hold))
+        {
+            eprintln!(
+                "error: no selected implementation supports payload `{}` with hold `{}`",
+                payload, hold
+            );
+            process::exit(1);
         }
     }
+}
 
-    async fn get(&self) -> u64 {
-        let val = self.inner.read().await;
-        *val
-    }
-
-    async fn set(&self, val: u64) {
-        let mut curr = self.inner.write().await;
-        *curr = val;
-    }
+/// Starts an executor of `kind` with `threads` workers (one per core when
+/// `None`), exiting if it cannot be started.
+fn start_executor(kind: ExecutorKind, threads: Option<usize>) -> Box<dyn Executor> {
+    kind.build(threads).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    })
 }
 
-impl Clone for AsyncCounter {
-    fn clone(&self) -> AsyncCounter {
-        AsyncCounter {
-            inner: Arc::clone(&self.inner),
+fn list(registry: &Registry, patterns: &[Pattern]) {
+    for entry in registry.entries() {
+        if patterns.is_empty() || patterns.iter().any(|p| p.matches(entry.name)) {
+            println!("{:<24} {}", entry.name, entry.payloads().join(", "));
         }
     }
 }
 
-struct Counter {
-    inner: Arc<RwLock<u64>>,
-}
-
-impl Counter {
-    fn new() -> Self {
-        Counter {
-            inner: Arc::new(RwLock::new(0)),
+fn run(opts: RunOpts, registry: &Registry, reporter: &mut Reporter) {
+    let implementations = select(registry, &opts.selection);
+    check_supported(&implementations, &opts.payloads, opts.workload.hold);
+    let executor = start_executor(opts.workload.executor, opts.threads);
+    for &payload in &opts.payloads {
+        for read_write_ratio in opts.read_write_ratios() {
+            let workload = Workload {
+                max_counters: opts.max_counters,
+                per_counter_operations_cnt: opts.per_counter_operations_cnt,
+                read_write_ratio,
+                mix: opts.workload.mix,
+                hold: opts.workload.hold,
+                payload,
+                shards: opts.workload.shards.unwrap_or_else(num_cpus::get),
+            };
+            for entry in implementations
+                .iter()
+                .filter(|entry| entry.supports(payload) && entry.supports_hold(opts.workload.hold))
+            {
+                let reports = run_trials(entry, &*executor, workload, opts.trials);
+                for report in &reports {
+                    reporter.report(report);
+                }
+                reporter.summary(&reports);
+            }
         }
     }
-
-    async fn get(&self) -> u64 {
-        let val = self.inner.read().unwrap();
-        *val
-    }
-
-    async fn set(&self, val: u64) {
-        let mut curr = self.inner.write().unwrap();
-        *curr = val;
-    }
 }
 
-impl Clone for Counter {
-    fn clone(&self) -> Counter {
-        Counter {
-            inner: Arc::clone(&self.inner),
+fn run_scenario(path: &Path, registry: &Registry, reporter: &mut Reporter) {
+    let scenario = match Scenario::load(path, registry) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
         }
+    };
+    for run in &scenario.runs {
+        // Implementations and their payloads were checked against the
+        // registry on load.
+        let entry = registry.get(&run.implementation).unwrap();
+        let executor = start_executor(run.executor, run.threads);
+        let reports = run_trials(entry, &*executor, run.workload(), run.trials());
+        for report in &reports {
+            reporter.report(report);
+        }
+        reporter.summary(&reports);
     }
 }
 
-#[async_trait]
-trait CounterTrait: Clone + 'static + Send {
-    fn new() -> Self;
-    fn name() -> &'static str;
-    async fn get(&self) -> u64;
-    async fn set(&self, value: u64);
-}
-
-#[async_trait]
-impl CounterTrait for Counter {
-    fn name() -> &'static str {
-        "Counter"
-    }
-
-    fn new() -> Self {
-        Counter::new()
-    }
-
-    async fn get(&self) -> u64 {
-        self.get().await
+fn run_sweep(opts: SweepOpts, registry: &Registry, format: OutputFormat, reporter: &mut Reporter) {
+    let implementations = select(registry, &opts.selection);
+    check_supported(&implementations, &opts.payloads, opts.workload.hold);
+    let executor = start_executor(opts.workload.executor, None);
+    let points = sweep::points(
+        &opts.payloads,
+        &opts.max_counters.0,
+        &opts.per_counter_operations_cnt.0,
+        &opts.read_write_ratios.0,
+        opts.workload.mix,
+        opts.workload.hold,
+        opts.workload.shards.unwrap_or_else(num_cpus::get),
+    );
+    let mut table = Table::new(implementations.iter().map(|entry| entry.name).collect());
+    for point in points {
+        for entry in implementations
+            .iter()
+            .filter(|entry| entry.supports(point.payload) && entry.supports_hold(point.hold))
+        {
+            let reports = run_trials(entry, &*executor, point, opts.trials);
+            for report in &reports {
+                // The table summarises text runs, machine-readable formats get every report.
+                if format == OutputFormat::Text {
+                    reporter.collect(report);
+                } else {
+                    reporter.report(report);
+                }
+                table.add(report);
+            }
+        }
     }
-
-    async fn set(&self, value: u64) {
-        self.set(value).await
+    if format == OutputFormat::Text {
+        table.print();
     }
 }
 
-#[async_trait]
-impl CounterTrait for AsyncCounter {
-    fn name() -> &'static str {
-        "AsyncCounter"
+fn run_scale(opts: ScaleOpts, registry: &Registry, format: OutputFormat, reporter: &mut Reporter) {
+    let implementations = select(registry, &opts.selection);
+    check_supported(&implementations, &[opts.payload], opts.workload.hold);
+    let implementations = implementations
+        .into_iter()
+        .filter(|entry| entry.supports(opts.payload) && entry.supports_hold(opts.workload.hold))
+        .collect::<Vec<_>>();
+    let thread_counts = opts
+        .threads
+        .map(|threads| threads.0)
+        .unwrap_or_else(scale::default_thread_counts);
+    if let Err(e) = scale::validate(opts.workload.executor, &thread_counts) {
+        eprintln!("error: {}", e);
+        process::exit(1);
     }
-
-    fn new() -> Self {
-        AsyncCounter::new()
-    }
-
-    async fn get(&self) -> u64 {
-        self.get().await
+    let workload = Workload {
+        max_counters: opts.max_counters,
+        per_counter_operations_cnt: opts.per_counter_operations_cnt,
+        read_write_ratio: opts.read_write_ratio,
+        mix: opts.workload.mix,
+        hold: opts.workload.hold,
+        payload: opts.payload,
+        shards: opts.workload.shards.unwrap_or_else(num_cpus::get),
+    };
+    let mut curves = Curves::new(implementations.iter().map(|entry| entry.name).collect());
+    for threads in thread_counts {
+        let executor = start_executor(opts.workload.executor, Some(threads));
+        for entry in &implementations {
+            let reports = run_trials(entry, &*executor, workload, opts.trials);
+            for report in &reports {
+                // The curves summarise text runs, machine-readable formats get every report.
+                if format == OutputFormat::Text {
+                    reporter.collect(report);
+                } else {
+                    reporter.report(report);
+                }
+                curves.add(report);
+            }
+        }
     }
-
-    async fn set(&self, value: u64) {
-        self.set(value).await
+    if format == OutputFormat::Text {
+        curves.print();
     }
 }
 
-async fn test<C: CounterTrait>(
-    executor: &mut (dyn Executor + 'static),
-    max_counters: usize,
-    per_counter_operations_cnt: usize,
-    read_write_ratio: f64,
-) {
-    let mut rng = thread_rng();
-    let mut counters = Vec::new();
-    for _ in 0..max_counters {
-        counters.push(C::new());
+/// Compares against and then saves baselines, returns whether any scenario
+/// regressed. A baseline sharing no scenario with the run fails it, after
+/// saving, since nothing could be checked.
+fn handle_baselines(opts: &BaselineOpts, format: OutputFormat, reporter: &Reporter) -> bool {
+    let mut regressed = false;
+    let mut unmatched = false;
+    if let Some(name) = &opts.compare {
+        let baseline = baseline::load(&opts.dir, name).unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        });
+        let comparison = baseline::compare(&baseline, reporter.records(), opts.threshold);
+        // Keep stdout parseable for machine-readable formats.
+        let written = if format == OutputFormat::Text {
+            comparison.write(&mut io::stdout())
+        } else {
+            comparison.write(&mut io::stderr())
+        };
+        written.expect("failed to write the baseline comparison");
+        regressed = comparison.has_regression();
+        if comparison.is_empty() {
+            eprintln!(
+                "error: no scenario of this run is present in baseline `{}`",
+                name
+            );
+            unmatched = true;
+        }
     }
-
-    let mut futures = FuturesUnordered::new();
-    for index in 0..max_counters {
-        for _ in 0..per_counter_operations_cnt {
-            let counter = counters[index].clone();
-            let read = rng.gen_bool(read_write_ratio);
-            if read {
-                let fut = async move { counter.get().await };
-                futures.push(executor.spawn_with_handle(fut).unwrap());
-            } else {
-                let new_val = rng.gen_range(0, 10000);
-                let fut = async move {
-                    counter.set(new_val).await;
-                    new_val
-                };
-                futures.push(executor.spawn_with_handle(fut).unwrap());
-            };
+    if let Some(name) = &opts.save {
+        match baseline::save(&opts.dir, name, reporter.records()) {
+            Ok(path) => eprintln!("saved baseline `{}` to {}", name, path.display()),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
         }
     }
-    let start = Instant::now();
-    let results = futures.collect::<Vec<_>>().await;
-    let elapsed = start.elapsed();
-    println!(
-        "{}, time spent: {} milliseconds, ratio: {}, first val: {}",
-        C::name(),
-        elapsed.as_millis(),
-        read_write_ratio,
-        results[0]
-    );
+    if unmatched {
+        process::exit(1);
+    }
+    regressed
 }
 
-#[tokio::main]
-async fn main() {
-    let mut executor = tokio::executor::DefaultExecutor::current();
-    let max_counters = 100;
-    let per_counter_operations_cnt = 10000;
-    let read_write_ratio = 0.9;
-    test::<Counter>(
-        &mut executor,
-        max_counters,
-        per_counter_operations_cnt,
-        read_write_ratio,
-    )
-    .await;
-    test::<AsyncCounter>(
-        &mut executor,
-        max_counters,
-        per_counter_operations_cnt,
-        read_write_ratio,
-    )
-    .await;
-    let read_write_ratio = 0.5;
-    test::<Counter>(
-        &mut executor,
-        max_counters,
-        per_counter_operations_cnt,
-        read_write_ratio,
-    )
-        .await;
-    test::<AsyncCounter>(
-        &mut executor,
-        max_counters,
-        per_counter_operations_cnt,
-        read_write_ratio,
-    )
-        .await;
+fn main() {
+    let opt = Opt::from_args();
+    let registry = Registry::with_builtins();
+    let mut reporter = Reporter::new(opt.format);
+    match opt.command {
+        Command::Run(opts) => run(opts, &registry, &mut reporter),
+        Command::Scenario { path } => run_scenario(&path, &registry, &mut reporter),
+        Command::Sweep(opts) => run_sweep(opts, &registry, opt.format, &mut reporter),
+        Command::Scale(opts) => run_scale(opts, &registry, opt.format, &mut reporter),
+        Command::List { patterns } => {
+            list(&registry, &patterns);
+            return;
+        }
+    }
+    if handle_baselines(&opt.baseline, opt.format, &reporter) {
+        eprintln!(
+            "error: throughput regressed by more than {}% against the baseline",
+            opt.baseline.threshold
+        );
+        process::exit(2);
+    }
 }
@@ -0,0 +1,116 @@
+use crate::latency::OpKind;
+use rand::Rng;
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+
+/// Relative weights of the operations that modify the counter. Together
+/// they make up the `1 - read_write_ratio` share of a workload, e.g.
+/// `write=2,cas=1` turns two thirds of the modifications into plain writes
+/// and one third into compare-and-swap loops.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mix {
+    pub write: u32,
+    pub increment: u32,
+    pub cas: u32,
+    pub update: u32,
+}
+
+impl Mix {
+    fn weights(&self) -> [(OpKind, u32); 4] {
+        [
+            (OpKind::Write, self.write),
+            (OpKind::Increment, self.increment),
+            (OpKind::Cas, self.cas),
+            (OpKind::Update, self.update),
+        ]
+    }
+
+    fn total(&self) -> u32 {
+        self.weights().iter().map(|(_, weight)| weight).sum()
+    }
+
+    /// Operation of the next task: a read with probability `read_ratio`,
+    /// otherwise a modification picked by weight.
+    pub fn choose<R: Rng>(&self, rng: &mut R, read_ratio: f64) -> OpKind {
+        if rng.gen_bool(read_ratio) {
+            return OpKind::Read;
+        }
+        let mut pick = rng.gen_range(0, self.total());
+        for &(kind, weight) in self.weights().iter() {
+            if pick < weight {
+                return kind;
+            }
+            pick -= weight;
+        }
+        unreachable!("the pick is below the total weight")
+    }
+}
+
+impl Default for Mix {
+    fn default() -> Self {
+        Mix {
+            write: 1,
+            increment: 0,
+            cas: 0,
+            update: 0,
+        }
+    }
+}
+
+impl fmt::Display for Mix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts = self
+            .weights()
+            .iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(kind, weight)| format!("{}={}", kind.name(), weight))
+            .collect::<Vec<_>>();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+impl FromStr for Mix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut mix = Mix {
+            write: 0,
+            increment: 0,
+            cas: 0,
+            update: 0,
+        };
+        for item in s.split(',') {
+            let mut parts = item.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let weight = parts
+                .next()
+                .ok_or_else(|| format!("`{}` is not a name=weight pair", item))?
+                .trim()
+                .parse::<u32>()
+                .map_err(|e| format!("`{}` has an invalid weight: {}", item, e))?;
+            match name {
+                "write" => mix.write = weight,
+                "increment" => mix.increment = weight,
+                "cas" => mix.cas = weight,
+                "update" => mix.update = weight,
+                _ => {
+                    return Err(format!(
+                        "unknown operation `{}`, expected write, increment, cas or update",
+                        name
+                    ))
+                }
+            }
+        }
+        if mix.total() == 0 {
+            return Err("at least one operation needs a positive weight".to_string());
+        }
+        Ok(mix)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mix {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
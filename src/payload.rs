@@ -0,0 +1,174 @@
+use serde::{de, Deserialize, Deserializer};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+/// Byte array payload, `size` bytes long.
+pub type Bytes = Vec<u8>;
+
+/// Map payload with `size` entries.
+pub type Map = HashMap<u64, u64>;
+
+/// Value protected by a counter. Reads hand out a clone, so the cost of
+/// `clone` is paid inside the critical section, as it would be for real
+/// shared state.
+pub trait Payload: Clone + PartialEq + Send + Sync + 'static {
+    /// Payload family, as spelled in a `PayloadSpec`.
+    const KIND: &'static str;
+
+    /// Value derived from `seed`. `size` is the payload specific size from
+    /// the `PayloadSpec` and is ignored by fixed-size payloads.
+    fn generate(size: usize, seed: u64) -> Self;
+
+    /// Cheap fingerprint of the value, reported as the first value of a run.
+    fn digest(&self) -> u64;
+
+    /// Counter-like modification, bumps the fingerprint by one.
+    fn increment(&mut self);
+}
+
+impl Payload for u64 {
+    const KIND: &'static str = "u64";
+
+    fn generate(_: usize, seed: u64) -> Self {
+        seed
+    }
+
+    fn digest(&self) -> u64 {
+        *self
+    }
+
+    fn increment(&mut self) {
+        *self = self.wrapping_add(1);
+    }
+}
+
+impl Payload for Bytes {
+    const KIND: &'static str = "bytes";
+
+    fn generate(size: usize, seed: u64) -> Self {
+        let mut bytes = vec![seed as u8; size];
+        let prefix = seed.to_le_bytes();
+        let len = prefix.len().min(size);
+        bytes[..len].copy_from_slice(&prefix[..len]);
+        bytes
+    }
+
+    fn digest(&self) -> u64 {
+        let mut prefix = [0; 8];
+        let len = prefix.len().min(self.len());
+        prefix[..len].copy_from_slice(&self[..len]);
+        u64::from_le_bytes(prefix)
+    }
+
+    fn increment(&mut self) {
+        let next = self.digest().wrapping_add(1).to_le_bytes();
+        let len = next.len().min(self.len());
+        self[..len].copy_from_slice(&next[..len]);
+    }
+}
+
+impl Payload for Map {
+    const KIND: &'static str = "map";
+
+    fn generate(size: usize, seed: u64) -> Self {
+        (0..size as u64).map(|key| (key, seed)).collect()
+    }
+
+    fn digest(&self) -> u64 {
+        self.get(&0).cloned().unwrap_or(0)
+    }
+
+    fn increment(&mut self) {
+        let entry = self.entry(0).or_insert(0);
+        *entry = entry.wrapping_add(1);
+    }
+}
+
+/// Payload family and size a workload runs with: `u64`, `bytes:<size>`
+/// (e.g. `bytes:8`, `bytes:1k`, `bytes:64k`) or `map:<entries>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PayloadSpec {
+    U64,
+    Bytes(usize),
+    Map(usize),
+}
+
+impl PayloadSpec {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PayloadSpec::U64 => <u64 as Payload>::KIND,
+            PayloadSpec::Bytes(_) => <Bytes as Payload>::KIND,
+            PayloadSpec::Map(_) => <Map as Payload>::KIND,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match *self {
+            PayloadSpec::U64 => 0,
+            PayloadSpec::Bytes(size) | PayloadSpec::Map(size) => size,
+        }
+    }
+}
+
+impl Default for PayloadSpec {
+    fn default() -> Self {
+        PayloadSpec::U64
+    }
+}
+
+impl fmt::Display for PayloadSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PayloadSpec::U64 => write!(f, "u64"),
+            PayloadSpec::Bytes(size) if size >= 1024 && size % 1024 == 0 => {
+                write!(f, "bytes:{}k", size / 1024)
+            }
+            PayloadSpec::Bytes(size) => write!(f, "bytes:{}", size),
+            PayloadSpec::Map(entries) => write!(f, "map:{}", entries),
+        }
+    }
+}
+
+impl FromStr for PayloadSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let kind = parts.next().unwrap_or("");
+        let size = parts.next();
+        match (kind, size) {
+            ("u64", None) => Ok(PayloadSpec::U64),
+            ("bytes", Some(size)) => parse_size(size).map(PayloadSpec::Bytes),
+            ("map", Some(entries)) => parse_size(entries).map(PayloadSpec::Map),
+            _ => Err(format!(
+                "unknown payload `{}`, expected u64, bytes:<size> or map:<entries>",
+                s
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PayloadSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Parses a positive size with an optional binary `k` or `m` suffix.
+fn parse_size(s: &str) -> Result<usize, String> {
+    let (digits, multiplier) = if s.ends_with('k') {
+        (&s[..s.len() - 1], 1024)
+    } else if s.ends_with('m') {
+        (&s[..s.len() - 1], 1024 * 1024)
+    } else {
+        (s, 1)
+    };
+    let size = digits
+        .parse::<usize>()
+        .map_err(|e| format!("`{}` is not a valid size: {}", s, e))?;
+    if size == 0 {
+        return Err("payload size must be greater than zero".to_string());
+    }
+    size.checked_mul(multiplier)
+        .ok_or_else(|| format!("payload size `{}` is too large", s))
+}
@@ -0,0 +1,214 @@
+use crate::{hold::Hold, latency, mix::Mix, payload::PayloadSpec, report::Report};
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env};
+
+/// Flat, serializable view of a `Report` plus the context needed to compare
+/// runs coming from different machines and revisions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub implementation: String,
+    pub counters: usize,
+    pub ops_per_counter: usize,
+    pub read_ratio: f64,
+    /// Weights of the modifying operations, e.g. `write=1,cas=1`.
+    #[serde(default = "default_mix")]
+    pub mix: String,
+    /// What writers did while holding the lock, e.g. `spin:1us`.
+    #[serde(default = "default_hold")]
+    pub hold: String,
+    /// Payload as spelled on the command line, e.g. `bytes:1k`.
+    #[serde(default = "default_payload")]
+    pub payload: String,
+    /// Shards of sharded implementations, missing for the others.
+    #[serde(default)]
+    pub shards: Option<usize>,
+    pub executor: String,
+    /// Worker threads of the executor, zero for records that predate the
+    /// setting and ran one worker per core of `host`.
+    #[serde(default)]
+    pub threads: usize,
+    pub wall_time_ms: f64,
+    pub throughput_ops_per_sec: f64,
+    pub latency_us: BTreeMap<String, Percentiles>,
+    /// Implementation specific counters, e.g. seqlock read retries.
+    #[serde(default)]
+    pub metrics: BTreeMap<String, u64>,
+    pub host: Host,
+    pub git_revision: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub count: u64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub p999: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Host {
+    pub hostname: String,
+    pub os: String,
+    pub arch: String,
+    pub cpus: usize,
+}
+
+impl Host {
+    pub fn current() -> Self {
+        Host {
+            hostname: hostname::get_hostname().unwrap_or_else(|| "unknown".to_string()),
+            os: env::consts::OS.to_string(),
+            arch: env::consts::ARCH.to_string(),
+            cpus: num_cpus::get(),
+        }
+    }
+}
+
+impl Percentiles {
+    fn from_histogram(histogram: &Histogram<u64>) -> Self {
+        Percentiles {
+            count: histogram.len(),
+            p50: latency::percentile_micros(histogram, 50.0),
+            p90: latency::percentile_micros(histogram, 90.0),
+            p99: latency::percentile_micros(histogram, 99.0),
+            p999: latency::percentile_micros(histogram, 99.9),
+            max: latency::max_micros(histogram),
+        }
+    }
+}
+
+impl Record {
+    pub fn new(report: &Report, host: &Host) -> Self {
+        let latency_us = report
+            .latencies
+            .histograms()
+            .into_iter()
+            .map(|(name, histogram)| (name.to_string(), Percentiles::from_histogram(histogram)))
+            .collect();
+        Record {
+            implementation: report.name.to_string(),
+            counters: report.max_counters,
+            ops_per_counter: report.per_counter_operations_cnt,
+            read_ratio: report.read_write_ratio,
+            mix: report.mix.to_string(),
+            hold: report.hold.to_string(),
+            payload: report.payload.to_string(),
+            shards: report.shards,
+            executor: report.executor.to_string(),
+            threads: report.threads,
+            wall_time_ms: report.elapsed.as_secs_f64() * 1000.0,
+            throughput_ops_per_sec: report.throughput(),
+            latency_us,
+            metrics: report
+                .metrics
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect(),
+            host: host.clone(),
+            git_revision: git_revision().to_string(),
+        }
+    }
+
+    pub fn csv_header(&self) -> String {
+        let mut columns = vec![
+            "implementation",
+            "counters",
+            "ops_per_counter",
+            "read_ratio",
+            "mix",
+            "hold",
+            "payload",
+            "shards",
+            "executor",
+            "threads",
+            "wall_time_ms",
+            "throughput_ops_per_sec",
+        ]
+        .into_iter()
+        .map(|column| column.to_string())
+        .collect::<Vec<_>>();
+        for name in self.latency_us.keys() {
+            for stat in &["count", "p50", "p90", "p99", "p999", "max"] {
+                columns.push(format!("{}_{}_us", name, stat));
+            }
+        }
+        for column in &["metrics", "hostname", "os", "arch", "cpus", "git_revision"] {
+            columns.push(column.to_string());
+        }
+        columns.join(",")
+    }
+
+    pub fn csv_row(&self) -> String {
+        let mut fields = vec![
+            csv_field(&self.implementation),
+            self.counters.to_string(),
+            self.ops_per_counter.to_string(),
+            self.read_ratio.to_string(),
+            csv_field(&self.mix),
+            csv_field(&self.hold),
+            csv_field(&self.payload),
+            self.shards
+                .map_or_else(String::new, |shards| shards.to_string()),
+            csv_field(&self.executor),
+            self.threads.to_string(),
+            format!("{:.3}", self.wall_time_ms),
+            format!("{:.1}", self.throughput_ops_per_sec),
+        ];
+        for percentiles in self.latency_us.values() {
+            fields.push(percentiles.count.to_string());
+            for value in &[
+                percentiles.p50,
+                percentiles.p90,
+                percentiles.p99,
+                percentiles.p999,
+                percentiles.max,
+            ] {
+                fields.push(format!("{:.3}", value));
+            }
+        }
+        // Metrics differ between implementations, so they share one column.
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>();
+        fields.push(csv_field(&metrics.join(";")));
+        fields.push(csv_field(&self.host.hostname));
+        fields.push(csv_field(&self.host.os));
+        fields.push(csv_field(&self.host.arch));
+        fields.push(self.host.cpus.to_string());
+        fields.push(csv_field(&self.git_revision));
+        fields.join(",")
+    }
+}
+
+/// Records written before the mix was configurable only had plain writes.
+fn default_mix() -> String {
+    Mix::default().to_string()
+}
+
+/// Records written before hold models existed released the lock right away.
+fn default_hold() -> String {
+    Hold::default().to_string()
+}
+
+/// Records written before payloads were configurable all ran with `u64`.
+fn default_payload() -> String {
+    PayloadSpec::U64.to_string()
+}
+
+/// Revision the binary was built from, captured by the build script.
+pub fn git_revision() -> &'static str {
+    option_env!("GIT_REVISION").unwrap_or("unknown")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(|c: char| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
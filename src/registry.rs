@@ -0,0 +1,152 @@
+use crate::{
+    counters::CounterTrait,
+    driver::Workload,
+    executor::Executor,
+    hold::Hold,
+    payload::{Payload, PayloadSpec},
+    report::Report,
+};
+use glob::Pattern;
+use std::{future::Future, pin::Pin};
+
+pub type RunFuture<'a> = Pin<Box<dyn Future<Output = Report> + 'a>>;
+
+/// Type-erased `test::<P, C>` for one implementation and payload.
+pub type RunFn = for<'a> fn(&'a dyn Executor, Workload) -> RunFuture<'a>;
+
+pub struct Entry {
+    pub name: &'static str,
+    /// `CounterTrait::supports_hold` of the implementation.
+    holds: fn(Hold) -> bool,
+    /// One run function per supported payload kind.
+    runs: Vec<(&'static str, RunFn)>,
+}
+
+impl Entry {
+    pub fn supports(&self, payload: PayloadSpec) -> bool {
+        self.run_fn(payload).is_some()
+    }
+
+    pub fn supports_hold(&self, hold: Hold) -> bool {
+        (self.holds)(hold)
+    }
+
+    /// Payload kinds this implementation can hold, in registration order.
+    pub fn payloads(&self) -> Vec<&'static str> {
+        self.runs.iter().map(|(kind, _)| *kind).collect()
+    }
+
+    /// Panics if the implementation does not support the workload's payload
+    /// or hold model, check `supports` and `supports_hold` first.
+    pub fn run<'a>(&self, executor: &'a dyn Executor, workload: Workload) -> RunFuture<'a> {
+        let run = self.run_fn(workload.payload).unwrap_or_else(|| {
+            panic!(
+                "implementation `{}` does not support payload `{}`",
+                self.name, workload.payload
+            )
+        });
+        assert!(
+            self.supports_hold(workload.hold),
+            "implementation `{}` does not support hold `{}`",
+            self.name,
+            workload.hold
+        );
+        run(executor, workload)
+    }
+
+    fn run_fn(&self, payload: PayloadSpec) -> Option<RunFn> {
+        self.runs
+            .iter()
+            .find(|(kind, _)| *kind == payload.kind())
+            .map(|(_, run)| *run)
+    }
+}
+
+fn run_erased<P: Payload, C: CounterTrait<P>>(
+    executor: &dyn Executor,
+    workload: Workload,
+) -> RunFuture<'_> {
+    Box::pin(crate::driver::test::<P, C>(executor, workload))
+}
+
+/// Implementations known to the harness, keyed by `CounterTrait::name`, in
+/// registration order.
+#[derive(Default)]
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Registry holding every implementation shipped with the harness.
+    pub fn with_builtins() -> Self {
+        let mut registry = Registry::new();
+        crate::counters::register_builtins(&mut registry);
+        registry
+    }
+
+    /// Registers `C` holding payload `P`. Registering the same counter for
+    /// further payloads adds them to its existing entry.
+    pub fn register<P: Payload, C: CounterTrait<P>>(&mut self) {
+        let index = match self
+            .entries
+            .iter()
+            .position(|entry| entry.name == C::name())
+        {
+            Some(index) => index,
+            None => {
+                self.entries.push(Entry {
+                    name: C::name(),
+                    holds: C::supports_hold,
+                    runs: Vec::new(),
+                });
+                self.entries.len() - 1
+            }
+        };
+        let entry = &mut self.entries[index];
+        assert!(
+            entry.runs.iter().all(|(kind, _)| *kind != P::KIND),
+            "implementation `{}` is registered twice for payload `{}`",
+            C::name(),
+            P::KIND
+        );
+        entry.runs.push((P::KIND, run_erased::<P, C>));
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Entries matching any of `include` (all when empty) and none of
+    /// `exclude`. Every pattern has to match at least one implementation, so
+    /// typos are reported instead of silently selecting nothing.
+    pub fn select(&self, include: &[Pattern], exclude: &[Pattern]) -> Result<Vec<&Entry>, String> {
+        for pattern in include.iter().chain(exclude) {
+            if !self.entries.iter().any(|entry| pattern.matches(entry.name)) {
+                return Err(format!(
+                    "`{}` does not match any implementation, see the `list` command",
+                    pattern
+                ));
+            }
+        }
+        let selected = self
+            .entries
+            .iter()
+            .filter(|entry| include.is_empty() || include.iter().any(|p| p.matches(entry.name)))
+            .filter(|entry| !exclude.iter().any(|p| p.matches(entry.name)))
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return Err("the patterns exclude every implementation".to_string());
+        }
+        Ok(selected)
+    }
+}
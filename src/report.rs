@@ -0,0 +1,193 @@
+use crate::{
+    executor::ExecutorKind,
+    hold::Hold,
+    latency::{self, Latencies, PERCENTILES},
+    mix::Mix,
+    payload::PayloadSpec,
+    record::{Host, Record},
+    stats::Summary,
+};
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
+
+pub struct Report {
+    pub name: &'static str,
+    pub max_counters: usize,
+    pub per_counter_operations_cnt: usize,
+    pub read_write_ratio: f64,
+    pub mix: Mix,
+    pub hold: Hold,
+    pub payload: PayloadSpec,
+    /// Shards of sharded implementations, `None` for the others.
+    pub shards: Option<usize>,
+    pub executor: ExecutorKind,
+    /// Worker threads of the executor.
+    pub threads: usize,
+    pub elapsed: Duration,
+    pub first_val: u64,
+    pub latencies: Latencies,
+    pub metrics: BTreeMap<&'static str, u64>,
+}
+
+impl Report {
+    pub fn total_ops(&self) -> usize {
+        self.max_counters * self.per_counter_operations_cnt
+    }
+
+    /// Operations per second over the measured region.
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.total_ops() as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Csv,
+    JsonLines,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "csv" => Ok(OutputFormat::Csv),
+            "jsonl" => Ok(OutputFormat::JsonLines),
+            _ => Err(format!(
+                "unknown output format `{}`, expected text, csv or jsonl",
+                s
+            )),
+        }
+    }
+}
+
+pub struct Reporter {
+    format: OutputFormat,
+    host: Host,
+    header_written: bool,
+    records: Vec<Record>,
+}
+
+impl Reporter {
+    pub fn new(format: OutputFormat) -> Self {
+        Reporter {
+            format,
+            host: Host::current(),
+            header_written: false,
+            records: Vec::new(),
+        }
+    }
+
+    pub fn report(&mut self, report: &Report) {
+        let record = Record::new(report, &self.host);
+        match self.format {
+            OutputFormat::Text => {
+                println!(
+                    "{}, executor: {}, threads: {}, time spent: {} milliseconds, ratio: {}, mix: {}, hold: {}, \
+                     payload: {}, first val: {}",
+                    report.name,
+                    report.executor,
+                    report.threads,
+                    report.elapsed.as_millis(),
+                    report.read_write_ratio,
+                    report.mix,
+                    report.hold,
+                    report.payload,
+                    report.first_val
+                );
+                for (name, histogram) in report.latencies.histograms() {
+                    if histogram.len() == 0 {
+                        continue;
+                    }
+                    let mut line = format!("    {:<12} n={:<9}", name, histogram.len());
+                    for &percentile in PERCENTILES {
+                        line.push_str(&format!(
+                            " p{}={:.1}us",
+                            percentile,
+                            latency::percentile_micros(histogram, percentile)
+                        ));
+                    }
+                    line.push_str(&format!(" max={:.1}us", latency::max_micros(histogram)));
+                    println!("{}", line);
+                }
+                if !report.metrics.is_empty() {
+                    let metrics = report
+                        .metrics
+                        .iter()
+                        .map(|(name, value)| format!("{}={}", name, value))
+                        .collect::<Vec<_>>();
+                    println!("    {}", metrics.join(" "));
+                }
+            }
+            OutputFormat::Csv => {
+                if !self.header_written {
+                    println!("{}", record.csv_header());
+                    self.header_written = true;
+                }
+                println!("{}", record.csv_row());
+            }
+            OutputFormat::JsonLines => {
+                println!("{}", serde_json::to_string(&record).unwrap());
+            }
+        }
+        self.records.push(record);
+    }
+
+    /// Keeps the record of `report` without printing it.
+    pub fn collect(&mut self, report: &Report) {
+        self.records.push(Record::new(report, &self.host));
+    }
+
+    /// Prints throughput statistics over the repetitions of one scenario.
+    /// Machine-readable formats already carry every repetition, so only the
+    /// text format gets the summary.
+    pub fn summary(&self, reports: &[Report]) {
+        if self.format != OutputFormat::Text || reports.is_empty() {
+            return;
+        }
+        let throughputs = reports.iter().map(Report::throughput).collect::<Vec<_>>();
+        let summary = Summary::new(&throughputs);
+        println!(
+            "{} over {} runs, throughput (op/s): mean {:.0} [{:.0} {:.0}] median {:.0} \
+             std dev {:.0} min {:.0} max {:.0}",
+            reports[0].name,
+            summary.samples,
+            summary.mean,
+            summary.ci_low,
+            summary.ci_high,
+            summary.median,
+            summary.std_dev,
+            summary.min,
+            summary.max
+        );
+        let outliers = summary.outliers;
+        if outliers.total() > 0 {
+            println!(
+                "    found {} outliers among {} runs: {} low severe, {} low mild, \
+                 {} high mild, {} high severe",
+                outliers.total(),
+                summary.samples,
+                outliers.low_severe,
+                outliers.low_mild,
+                outliers.high_mild,
+                outliers.high_severe
+            );
+        }
+        let worst_stall = reports
+            .iter()
+            .map(|report| latency::max_micros(&report.latencies.stall))
+            .fold(0.0, f64::max);
+        println!("    worst executor stall: {:.1}us", worst_stall);
+    }
+
+    /// Records of every report seen so far, in reporting order.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{executor::ExecutorKind, report::Report};
+use std::collections::BTreeMap;
+
+/// Thread counts a scaling run uses unless told otherwise: powers of two
+/// below the number of cores followed by the core count, e.g. 1, 2, 4, 6 on
+/// six cores.
+pub fn default_thread_counts() -> Vec<usize> {
+    let cores = num_cpus::get();
+    let mut counts = Vec::new();
+    let mut threads = 1;
+    while threads < cores {
+        counts.push(threads);
+        threads *= 2;
+    }
+    counts.push(cores);
+    counts
+}
+
+/// Checks every step of a scaling run before the first one starts, so a
+/// bad thread count does not abort the run halfway through.
+pub fn validate(executor: ExecutorKind, thread_counts: &[usize]) -> Result<(), String> {
+    if !executor.scales() {
+        return Err(format!(
+            "{} cannot change its number of threads, pick another executor to scale",
+            executor
+        ));
+    }
+    for &threads in thread_counts {
+        executor.validate_threads(threads)?;
+    }
+    Ok(())
+}
+
+/// Collects scaling reports and renders a throughput-versus-threads curve
+/// per implementation. Repetitions are shown as their median. Speedup and
+/// parallel efficiency are relative to the smallest thread count measured,
+/// normally the single-threaded run: a perfectly scaling lock keeps an
+/// efficiency of 100%.
+pub struct Curves {
+    implementations: Vec<&'static str>,
+    throughputs: BTreeMap<&'static str, BTreeMap<usize, Vec<f64>>>,
+}
+
+impl Curves {
+    pub fn new(implementations: Vec<&'static str>) -> Self {
+        Curves {
+            implementations,
+            throughputs: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, report: &Report) {
+        self.throughputs
+            .entry(report.name)
+            .or_insert_with(BTreeMap::new)
+            .entry(report.threads)
+            .or_insert_with(Vec::new)
+            .push(report.throughput());
+    }
+
+    pub fn print(&self) {
+        for name in &self.implementations {
+            let curve = match self.throughputs.get(name) {
+                Some(curve) => curve,
+                None => continue,
+            };
+            println!("{}", name);
+            println!(
+                "{:>10} {:>14} {:>10} {:>11}",
+                "threads", "op/s", "speedup", "efficiency"
+            );
+            let mut base = None;
+            for (&threads, throughputs) in curve {
+                let throughput = median(throughputs);
+                let (base_threads, base_throughput) = *base.get_or_insert((threads, throughput));
+                let speedup = if base_throughput > 0.0 {
+                    throughput / base_throughput
+                } else {
+                    0.0
+                };
+                let efficiency = speedup * base_threads as f64 / threads as f64;
+                println!(
+                    "{:>10} {:>14.0} {:>9.2}x {:>10.1}%",
+                    threads,
+                    throughput,
+                    speedup,
+                    efficiency * 100.0
+                );
+            }
+        }
+    }
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted[sorted.len() / 2]
+}
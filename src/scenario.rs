@@ -0,0 +1,148 @@
+use crate::{
+    driver::{validate_count, validate_ratio, Trials, Workload},
+    executor::ExecutorKind,
+    hold::Hold,
+    mix::Mix,
+    payload::PayloadSpec,
+    registry::Registry,
+};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(rename = "run", default)]
+    pub runs: Vec<Run>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Run {
+    pub implementation: String,
+    #[serde(default = "default_counters")]
+    pub counters: usize,
+    #[serde(default = "default_ops_per_counter")]
+    pub ops_per_counter: usize,
+    #[serde(default = "default_read_ratio")]
+    pub read_ratio: f64,
+    #[serde(default)]
+    pub mix: Mix,
+    #[serde(default)]
+    pub hold: Hold,
+    #[serde(default)]
+    pub payload: PayloadSpec,
+    /// Shards of sharded implementations, one per core when missing.
+    #[serde(default)]
+    pub shards: Option<usize>,
+    #[serde(default)]
+    pub executor: ExecutorKind,
+    /// Worker threads of the executor, one per core when missing.
+    #[serde(default)]
+    pub threads: Option<usize>,
+    #[serde(default = "default_warmup")]
+    pub warmup: usize,
+    #[serde(default = "default_repetitions")]
+    pub repetitions: usize,
+}
+
+fn default_counters() -> usize {
+    100
+}
+
+fn default_ops_per_counter() -> usize {
+    10000
+}
+
+fn default_read_ratio() -> f64 {
+    0.9
+}
+
+fn default_warmup() -> usize {
+    1
+}
+
+fn default_repetitions() -> usize {
+    5
+}
+
+impl Scenario {
+    pub fn load(path: &Path, registry: &Registry) -> Result<Scenario, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let scenario = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str::<Scenario>(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str::<Scenario>(&content).map_err(|e| e.to_string()),
+            _ => Err("expected a .toml or .json file".to_string()),
+        }
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+        scenario.validate(registry)?;
+        Ok(scenario)
+    }
+
+    fn validate(&self, registry: &Registry) -> Result<(), String> {
+        if self.runs.is_empty() {
+            return Err("scenario does not contain any runs".to_string());
+        }
+        for (index, run) in self.runs.iter().enumerate() {
+            run.validate(registry)
+                .map_err(|e| format!("run #{} ({}): {}", index + 1, run.implementation, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Run {
+    pub fn trials(&self) -> Trials {
+        Trials {
+            warmup: self.warmup,
+            repetitions: self.repetitions,
+        }
+    }
+
+    pub fn workload(&self) -> Workload {
+        Workload {
+            max_counters: self.counters,
+            per_counter_operations_cnt: self.ops_per_counter,
+            read_write_ratio: self.read_ratio,
+            mix: self.mix,
+            hold: self.hold,
+            payload: self.payload,
+            shards: self.shards.unwrap_or_else(num_cpus::get),
+        }
+    }
+
+    fn validate(&self, registry: &Registry) -> Result<(), String> {
+        match registry.get(&self.implementation) {
+            None => {
+                return Err(format!(
+                    "unknown implementation `{}`, see the `list` command",
+                    self.implementation
+                ))
+            }
+            Some(entry) if !entry.supports(self.payload) => {
+                return Err(format!(
+                    "payload `{}` is not supported, expected one of: {}",
+                    self.payload,
+                    entry.payloads().join(", ")
+                ))
+            }
+            Some(entry) if !entry.supports_hold(self.hold) => {
+                return Err(format!("hold `{}` is not supported", self.hold))
+            }
+            Some(_) => {}
+        }
+        validate_count(self.counters).map_err(|e| format!("counters: {}", e))?;
+        validate_count(self.ops_per_counter).map_err(|e| format!("ops_per_counter: {}", e))?;
+        validate_count(self.repetitions).map_err(|e| format!("repetitions: {}", e))?;
+        validate_ratio(self.read_ratio)?;
+        if let Some(shards) = self.shards {
+            validate_count(shards).map_err(|e| format!("shards: {}", e))?;
+        }
+        if let Some(threads) = self.threads {
+            validate_count(threads).map_err(|e| format!("threads: {}", e))?;
+            self.executor.validate_threads(threads)?;
+        }
+        Ok(())
+    }
+}
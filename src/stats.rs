@@ -0,0 +1,307 @@
+use rand::{thread_rng, Rng};
+use std::f64::consts::PI;
+
+pub fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+/// Unbiased sample variance, zero for fewer than two samples.
+pub fn variance(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(samples);
+    samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64
+}
+
+/// Two-sided p-value of Welch's t-test for the means of `a` and `b`, `None`
+/// when either side has fewer than two samples.
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let va = variance(a) / a.len() as f64;
+    let vb = variance(b) / b.len() as f64;
+    let diff = mean(a) - mean(b);
+    if va + vb == 0.0 {
+        return Some(if diff == 0.0 { 1.0 } else { 0.0 });
+    }
+    let t = diff / (va + vb).sqrt();
+    let df =
+        (va + vb).powi(2) / (va.powi(2) / (a.len() - 1) as f64 + vb.powi(2) / (b.len() - 1) as f64);
+    Some(incomplete_beta(df / 2.0, 0.5, df / (df + t * t)))
+}
+
+/// Lanczos approximation of ln(Γ(x)).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        return PI.ln() - (PI * x).sin().ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let mut sum = COEFFICIENTS[0];
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += coefficient / (x + i as f64);
+    }
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b).
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly only on this side of the mean.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Lentz's method for the continued fraction of the incomplete beta function.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 200;
+    const EPSILON: f64 = 3e-14;
+    const TINY: f64 = 1e-300;
+
+    let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut result = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        result *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        result *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    result
+}
+
+/// Percentile of already sorted samples with linear interpolation.
+fn percentile_of_sorted(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.len() == 1 {
+        return sorted[0];
+    }
+    let rank = percentile / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+fn sorted(samples: &[f64]) -> Vec<f64> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted
+}
+
+/// Samples outside Tukey's fences, the same classification criterion uses:
+/// mild beyond 1.5 IQR from the quartiles, severe beyond 3 IQR.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Outliers {
+    pub low_severe: usize,
+    pub low_mild: usize,
+    pub high_mild: usize,
+    pub high_severe: usize,
+}
+
+impl Outliers {
+    fn classify(sorted: &[f64]) -> Self {
+        let q1 = percentile_of_sorted(sorted, 25.0);
+        let q3 = percentile_of_sorted(sorted, 75.0);
+        let iqr = q3 - q1;
+        let mut outliers = Outliers::default();
+        for &sample in sorted {
+            if sample < q1 - 3.0 * iqr {
+                outliers.low_severe += 1;
+            } else if sample < q1 - 1.5 * iqr {
+                outliers.low_mild += 1;
+            } else if sample > q3 + 3.0 * iqr {
+                outliers.high_severe += 1;
+            } else if sample > q3 + 1.5 * iqr {
+                outliers.high_mild += 1;
+            }
+        }
+        outliers
+    }
+
+    pub fn total(&self) -> usize {
+        self.low_severe + self.low_mild + self.high_mild + self.high_severe
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+    pub samples: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Bootstrap 95% confidence interval of the mean.
+    pub ci_low: f64,
+    pub ci_high: f64,
+    pub outliers: Outliers,
+}
+
+const BOOTSTRAP_RESAMPLES: usize = 10_000;
+
+impl Summary {
+    /// Panics on an empty slice, there is nothing to summarise.
+    pub fn new(samples: &[f64]) -> Self {
+        assert!(!samples.is_empty(), "cannot summarise zero samples");
+        let sorted = sorted(samples);
+        let (ci_low, ci_high) = bootstrap_mean_ci(samples, 0.95);
+        Summary {
+            samples: samples.len(),
+            mean: mean(samples),
+            median: percentile_of_sorted(&sorted, 50.0),
+            std_dev: variance(samples).sqrt(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            ci_low,
+            ci_high,
+            outliers: Outliers::classify(&sorted),
+        }
+    }
+}
+
+/// Percentile bootstrap of the mean.
+fn bootstrap_mean_ci(samples: &[f64], confidence: f64) -> (f64, f64) {
+    if samples.len() < 2 {
+        return (samples[0], samples[0]);
+    }
+    let mut rng = thread_rng();
+    let mut means = Vec::with_capacity(BOOTSTRAP_RESAMPLES);
+    for _ in 0..BOOTSTRAP_RESAMPLES {
+        let mut sum = 0.0;
+        for _ in 0..samples.len() {
+            sum += samples[rng.gen_range(0, samples.len())];
+        }
+        means.push(sum / samples.len() as f64);
+    }
+    let means = sorted(&means);
+    let tail = (1.0 - confidence) / 2.0 * 100.0;
+    (
+        percentile_of_sorted(&means, tail),
+        percentile_of_sorted(&means, 100.0 - tail),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reference values that have no closed form come from mpmath.
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn ln_gamma_matches_known_values() {
+        assert_close(ln_gamma(1.0), 0.0, 1e-12);
+        assert_close(ln_gamma(5.0), 24f64.ln(), 1e-12);
+        assert_close(ln_gamma(0.5), PI.sqrt().ln(), 1e-12);
+        // Below 0.5 the reflection formula kicks in.
+        assert_close(ln_gamma(0.25), 1.288_022_524_698_077, 1e-12);
+    }
+
+    #[test]
+    fn incomplete_beta_matches_closed_forms() {
+        assert_close(incomplete_beta(1.0, 1.0, 0.3), 0.3, 1e-12);
+        assert_close(incomplete_beta(3.0, 1.0, 0.5), 0.125, 1e-12);
+        assert_close(incomplete_beta(2.5, 2.5, 0.5), 0.5, 1e-12);
+        assert_close(incomplete_beta(2.0, 3.0, 0.4), 0.5248, 1e-12);
+        assert_close(incomplete_beta(2.0, 3.0, 0.0), 0.0, 0.0);
+        assert_close(incomplete_beta(2.0, 3.0, 1.0), 1.0, 0.0);
+    }
+
+    #[test]
+    fn t_distribution_tails_for_one_and_two_degrees_of_freedom() {
+        // Two-sided p-value of t = 1: 1/2 for the Cauchy distribution with one
+        // degree of freedom, 1 - 1/sqrt(3) with two.
+        assert_close(incomplete_beta(0.5, 0.5, 0.5), 0.5, 1e-12);
+        assert_close(
+            incomplete_beta(1.0, 0.5, 2.0 / 3.0),
+            1.0 - 1.0 / 3f64.sqrt(),
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn welch_t_test_matches_reference_p_values() {
+        let p = welch_t_test(&[1.0, 2.0, 3.0, 4.0, 5.0], &[2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_close(p.unwrap(), 0.107_531_194_930_627, 1e-10);
+
+        let a = [19.8, 20.4, 19.6, 17.8, 18.5, 18.9, 18.3, 18.9, 19.5, 22.0];
+        let b = [
+            28.2, 26.6, 20.1, 23.3, 25.2, 22.1, 17.7, 27.6, 20.6, 13.7, 23.2, 17.5, 20.6, 18.0,
+            23.9, 21.6, 24.3, 20.4, 23.9, 13.3,
+        ];
+        assert_close(welch_t_test(&a, &b).unwrap(), 0.035_484_530_830_010, 1e-10);
+    }
+
+    #[test]
+    fn welch_t_test_with_small_degrees_of_freedom() {
+        // About 1.47 degrees of freedom.
+        let p = welch_t_test(&[1.0, 2.0], &[3.0, 5.0]);
+        assert_close(p.unwrap(), 0.198_727_388_934_526, 1e-10);
+    }
+
+    #[test]
+    fn welch_t_test_of_equal_samples_is_not_significant() {
+        let samples = [3.0, 5.0, 4.0, 6.0];
+        assert_close(welch_t_test(&samples, &samples).unwrap(), 1.0, 1e-12);
+    }
+
+    #[test]
+    fn welch_t_test_with_zero_variance() {
+        assert_close(
+            welch_t_test(&[2.0, 2.0], &[2.0, 2.0, 2.0]).unwrap(),
+            1.0,
+            0.0,
+        );
+        assert_close(
+            welch_t_test(&[2.0, 2.0], &[3.0, 3.0, 3.0]).unwrap(),
+            0.0,
+            0.0,
+        );
+    }
+
+    #[test]
+    fn welch_t_test_needs_two_samples_per_side() {
+        assert_eq!(welch_t_test(&[1.0], &[1.0, 2.0]), None);
+        assert_eq!(welch_t_test(&[1.0, 2.0], &[]), None);
+    }
+}
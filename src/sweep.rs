@@ -0,0 +1,214 @@
+use crate::{
+    driver::{validate_count, validate_ratio, Workload},
+    hold::Hold,
+    mix::Mix,
+    payload::PayloadSpec,
+    report::Report,
+};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// List of values given either explicitly (`1,10,100`), as an arithmetic
+/// range (`0.1:0.9:0.1`) or as a geometric one (`1:1000:*10`). Both range
+/// forms include the end value when the steps land on it.
+#[derive(Debug, Clone)]
+pub struct Values<T>(pub Vec<T>);
+
+impl FromStr for Values<f64> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = parse_values(s)?
+            .into_iter()
+            .map(validate_ratio)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Values(values))
+    }
+}
+
+impl FromStr for Values<usize> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = parse_values(s)?
+            .into_iter()
+            .map(|value| {
+                if value.fract() != 0.0 || value < 0.0 {
+                    return Err(format!("{} is not a whole number", value));
+                }
+                validate_count(value as usize)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Values(values))
+    }
+}
+
+fn parse_number(s: &str) -> Result<f64, String> {
+    s.trim()
+        .parse::<f64>()
+        .map_err(|e| format!("`{}` is not a valid number: {}", s, e))
+}
+
+fn parse_values(s: &str) -> Result<Vec<f64>, String> {
+    let mut values = Vec::new();
+    for item in s.split(',') {
+        let parts = item.split(':').collect::<Vec<_>>();
+        match parts.as_slice() {
+            [value] => values.push(parse_number(value)?),
+            [start, end, step] => {
+                let start = parse_number(start)?;
+                let end = parse_number(end)?;
+                if start > end {
+                    return Err(format!("range `{}` ends before it starts", item));
+                }
+                // Tolerate float rounding so that `0.1:0.9:0.1` still ends on 0.9.
+                let end_with_slack = end + (end - start).abs() * 1e-9;
+                let step = step.trim();
+                if step.starts_with('*') {
+                    let factor = parse_number(&step[1..])?;
+                    if factor <= 1.0 || start <= 0.0 {
+                        return Err(format!(
+                            "geometric range `{}` needs a positive start and a factor above 1",
+                            item
+                        ));
+                    }
+                    let mut value = start;
+                    while value <= end_with_slack {
+                        values.push(value);
+                        value *= factor;
+                    }
+                } else {
+                    let step = parse_number(step)?;
+                    if step <= 0.0 {
+                        return Err(format!("range `{}` needs a positive step", item));
+                    }
+                    let mut index = 0;
+                    loop {
+                        let value = start + step * index as f64;
+                        if value > end_with_slack {
+                            break;
+                        }
+                        // Round away the accumulated error, reports should show 0.3.
+                        values.push((value * 1e9).round() / 1e9);
+                        index += 1;
+                    }
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "`{}` is neither a value nor a start:end:step range",
+                    item
+                ))
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Cartesian product of the sweep dimensions in the order they were given,
+/// every point running with the same `mix`, `hold` and `shards`.
+pub fn points(
+    payloads: &[PayloadSpec],
+    counters: &[usize],
+    ops: &[usize],
+    ratios: &[f64],
+    mix: Mix,
+    hold: Hold,
+    shards: usize,
+) -> Vec<Workload> {
+    let mut points = Vec::new();
+    for &payload in payloads {
+        for &max_counters in counters {
+            for &per_counter_operations_cnt in ops {
+                for &read_write_ratio in ratios {
+                    points.push(Workload {
+                        max_counters,
+                        per_counter_operations_cnt,
+                        read_write_ratio,
+                        mix,
+                        hold,
+                        payload,
+                        shards,
+                    });
+                }
+            }
+        }
+    }
+    points
+}
+
+/// Collects sweep reports and renders one row per point with a column per
+/// implementation, so crossovers between implementations are easy to spot.
+/// Repetitions of a point are shown as their median.
+pub struct Table {
+    implementations: Vec<&'static str>,
+    rows: BTreeMap<(String, usize, usize, u64), BTreeMap<&'static str, Vec<u128>>>,
+}
+
+impl Table {
+    pub fn new(implementations: Vec<&'static str>) -> Self {
+        Table {
+            implementations,
+            rows: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, report: &Report) {
+        let key = (
+            report.payload.to_string(),
+            report.max_counters,
+            report.per_counter_operations_cnt,
+            report.read_write_ratio.to_bits(),
+        );
+        self.rows
+            .entry(key)
+            .or_insert_with(BTreeMap::new)
+            .entry(report.name)
+            .or_insert_with(Vec::new)
+            .push(report.elapsed.as_millis());
+    }
+
+    pub fn print(&self) {
+        let mut header = format!(
+            "{:>10} {:>10} {:>10} {:>7}",
+            "payload", "counters", "ops", "ratio"
+        );
+        for name in &self.implementations {
+            header.push_str(&format!(" {:>14}", name));
+        }
+        header.push_str(&format!(" {:>14}", "fastest"));
+        println!("{}", header);
+
+        for ((payload, max_counters, ops, ratio), repetitions) in &self.rows {
+            let timings = repetitions
+                .iter()
+                .map(|(name, millis)| (*name, median(millis)))
+                .collect::<BTreeMap<_, _>>();
+            let mut line = format!(
+                "{:>10} {:>10} {:>10} {:>7}",
+                payload,
+                max_counters,
+                ops,
+                f64::from_bits(*ratio)
+            );
+            for name in &self.implementations {
+                match timings.get(name) {
+                    Some(millis) => line.push_str(&format!(" {:>12}ms", millis)),
+                    None => line.push_str(&format!(" {:>14}", "-")),
+                }
+            }
+            let fastest = timings
+                .iter()
+                .min_by_key(|(_, millis)| **millis)
+                .map(|(name, _)| *name)
+                .unwrap_or("-");
+            line.push_str(&format!(" {:>14}", fastest));
+            println!("{}", line);
+        }
+    }
+}
+
+fn median(values: &[u128]) -> u128 {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    sorted[sorted.len() / 2]
+}
@@ -0,0 +1,69 @@
+use crate::{executor::Executor, latency};
+use futures::future::{join_all, RemoteHandle};
+use hdrhistogram::Histogram;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// How often the probe asks to be woken up. The timer runs on its own
+/// thread, so any lateness beyond its resolution is time the executor had
+/// no worker free to poll the probe.
+pub const PROBE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Probe tasks sharing the executor with the workload. Each sleeps for
+/// `PROBE_INTERVAL` over and over and records how late it wakes up, which
+/// shows worker threads stalled by blocking locks even when the lock
+/// operations themselves look fast.
+///
+/// One probe is spawned per worker. Executors that never move tasks between
+/// threads, like thread-per-core, deal them out round-robin so every core
+/// gets a probe; a single probe would only see the stalls of its own core.
+pub struct Watchdog {
+    stop: Arc<AtomicBool>,
+    probes: Vec<RemoteHandle<Histogram<u64>>>,
+}
+
+impl Watchdog {
+    pub fn spawn(executor: &dyn Executor) -> Watchdog {
+        let stop = Arc::new(AtomicBool::new(false));
+        let probes = (0..executor.threads())
+            .map(|_| executor.spawn_with_handle(probe(Arc::clone(&stop))))
+            .collect();
+        Watchdog { stop, probes }
+    }
+
+    /// Stops probing and returns the lateness of every wake-up of every
+    /// probe, in nanoseconds.
+    pub async fn stop(self) -> Histogram<u64> {
+        self.stop.store(true, Ordering::Relaxed);
+        let mut stalls = latency::new_histogram();
+        for probe in join_all(self.probes).await {
+            // Every probe records into a histogram with the same bounds.
+            stalls.add(&probe).unwrap();
+        }
+        stalls
+    }
+}
+
+async fn probe(stop: Arc<AtomicBool>) -> Histogram<u64> {
+    let mut stalls = latency::new_histogram();
+    loop {
+        let due = Instant::now() + PROBE_INTERVAL;
+        async_std::task::sleep(PROBE_INTERVAL).await;
+        // The last wake-up may fall after the measured region.
+        if stop.load(Ordering::Relaxed) {
+            return stalls;
+        }
+        let woken = Instant::now();
+        let late = if woken > due {
+            woken.duration_since(due)
+        } else {
+            Duration::from_secs(0)
+        };
+        stalls.saturating_record(latency::nanos(late));
+    }
+}